@@ -0,0 +1,314 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{
+    Attribute, DeriveInput, Ident, LitInt, LitStr, Path, Type, meta::ParseNestedMeta, parenthesized,
+};
+
+use crate::utils::{ascolt_path, async_trait_attr, parse_quotable, parse_variant};
+
+const OVERFLOWS: &[&str] = &["drop_oldest", "drop_newest", "block", "error"];
+
+/// Capacity of an actor's mailbox, from `mailbox = bounded(N)` or
+/// `mailbox = unbounded`.
+pub(crate) enum Mailbox {
+    Bounded(LitInt),
+    Unbounded,
+}
+
+/// Options of the `#[actor(...)]` attribute.
+#[derive(Default)]
+pub(crate) struct ActorArgs {
+    pub(crate) error_ty: Option<Type>,
+    pub(crate) handle: Option<Ident>,
+    pub(crate) on_start: Option<Ident>,
+    pub(crate) on_stop: Option<Ident>,
+    pub(crate) on_error: Option<Ident>,
+    pub(crate) krate: Option<Path>,
+    /// Name the actor is registered under in ascolt's registry.
+    pub(crate) name: Option<LitStr>,
+    pub(crate) mailbox: Option<Mailbox>,
+    /// Overflow policy of a bounded mailbox.
+    pub(crate) overflow: Option<Ident>,
+}
+
+impl ActorArgs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut args = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("actor")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("error") {
+                    args.error_ty = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("handle") {
+                    args.handle = Some(parse_quotable(&meta)?);
+                } else if meta.path.is_ident("on_start") {
+                    args.on_start = Some(parse_ident_str(&meta)?);
+                } else if meta.path.is_ident("on_stop") {
+                    args.on_stop = Some(parse_ident_str(&meta)?);
+                } else if meta.path.is_ident("on_error") {
+                    args.on_error = Some(parse_ident_str(&meta)?);
+                } else if meta.path.is_ident("crate") {
+                    args.krate = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("name") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    if name.value().is_empty() {
+                        return Err(syn::Error::new_spanned(name, "Actor name cannot be empty"));
+                    }
+                    args.name = Some(name);
+                } else if meta.path.is_ident("mailbox") {
+                    args.mailbox = Some(parse_mailbox(&meta)?);
+                } else if meta.path.is_ident("overflow") {
+                    let overflow: LitStr = meta.value()?.parse()?;
+                    args.overflow = Some(parse_variant(&overflow, OVERFLOWS)?);
+                } else {
+                    return Err(meta.error("unsupported attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(args)
+    }
+
+    pub(crate) fn require_error_ty(&self, name: &Ident) -> syn::Result<&Type> {
+        self.error_ty
+            .as_ref()
+            .ok_or_else(|| syn::Error::new_spanned(name, "missing #[actor(error = ...)]"))
+    }
+
+    /// Path of the ascolt crate, from `crate = ...` or the calling crate's
+    /// dependencies.
+    pub(crate) fn ascolt(&self) -> Path {
+        ascolt_path(self.krate.as_ref())
+    }
+}
+
+/// Parses `key = "ident"`, keeping the span of the string literal.
+fn parse_ident_str(meta: &ParseNestedMeta) -> syn::Result<Ident> {
+    let value: LitStr = meta.value()?.parse()?;
+    value.parse()
+}
+
+/// Parses `bounded(N)` or `unbounded`.
+fn parse_mailbox(meta: &ParseNestedMeta) -> syn::Result<Mailbox> {
+    let input = meta.value()?;
+    let kind: Ident = input.parse()?;
+
+    if kind == "bounded" {
+        let content;
+        parenthesized!(content in input);
+        let capacity: LitInt = content.parse()?;
+        if capacity.base10_parse::<usize>()? == 0 {
+            return Err(syn::Error::new_spanned(
+                capacity,
+                "Mailbox capacity must be at least 1",
+            ));
+        }
+        Ok(Mailbox::Bounded(capacity))
+    } else if kind == "unbounded" {
+        Ok(Mailbox::Unbounded)
+    } else {
+        Err(syn::Error::new_spanned(
+            kind,
+            "Expected bounded(capacity) or unbounded",
+        ))
+    }
+}
+
+pub(crate) fn expand_derive_actor(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+
+    let args = ActorArgs::parse(&input.attrs)?;
+    let error_ty = args.require_error_ty(name)?;
+    let ascolt = args.ascolt();
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let handle = args
+        .handle
+        .as_ref()
+        .map(|handle| expand_handle(&input, &args, handle, &ascolt));
+
+    let registration = args.name.as_ref().map(|actor_name| {
+        quote! {
+            impl #impl_generics #ascolt::registry::NamedActorTrait for #name #ty_generics #where_clause {
+                const NAME: &'static str = #actor_name;
+            }
+        }
+    });
+
+    let mailbox = expand_mailbox(&args, &ascolt)?;
+    let hooks = expand_hooks(&args, error_ty);
+    let async_trait = (!hooks.is_empty()).then(|| async_trait_attr(&ascolt));
+
+    let actor_error = cfg!(feature = "actor-error").then(|| {
+        quote! {
+            impl #impl_generics #ascolt::ActorErrorTrait for #name #ty_generics #where_clause {
+                type Error = #error_ty;
+            }
+        }
+    });
+
+    Ok(quote! {
+        #async_trait
+        impl #impl_generics #ascolt::ActorTrait<#error_ty> for #name #ty_generics #where_clause {
+            #mailbox
+            #hooks
+        }
+
+        #actor_error
+        #registration
+        #handle
+    })
+}
+
+/// Overrides the `ActorTrait::MAILBOX` configuration read by ascolt's spawn
+/// functions when `mailbox` or `overflow` is given.
+fn expand_mailbox(args: &ActorArgs, ascolt: &Path) -> syn::Result<Option<TokenStream>> {
+    let capacity = match (&args.mailbox, &args.overflow) {
+        (None, None) => return Ok(None),
+        (Some(Mailbox::Bounded(capacity)), _) => {
+            quote!(#ascolt::mailbox::MailboxConfig::bounded(#capacity))
+        }
+        (Some(Mailbox::Unbounded), None) => quote!(#ascolt::mailbox::MailboxConfig::unbounded()),
+        (_, Some(overflow)) => {
+            return Err(syn::Error::new(
+                overflow.span(),
+                "overflow requires a bounded mailbox, e.g. mailbox = bounded(1024)",
+            ));
+        }
+    };
+
+    let overflow = args
+        .overflow
+        .as_ref()
+        .map(|overflow| quote!(.with_overflow(#ascolt::mailbox::Overflow::#overflow)));
+
+    Ok(Some(quote! {
+        const MAILBOX: #ascolt::mailbox::MailboxConfig = #capacity #overflow;
+    }))
+}
+
+/// Overrides the `ActorTrait` lifecycle hooks named in `#[actor(...)]`.
+///
+/// The calls are spanned to the method names in the attribute, so a missing
+/// method or an incompatible signature is reported there.
+fn expand_hooks(args: &ActorArgs, error_ty: &Type) -> TokenStream {
+    let on_start = args.on_start.as_ref().map(|method| {
+        let call = quote_spanned!(method.span()=> Self::#method(self).await);
+        quote! {
+            async fn on_start(&mut self) -> ::core::result::Result<(), #error_ty> {
+                #call
+            }
+        }
+    });
+
+    let on_stop = args.on_stop.as_ref().map(|method| {
+        let call = quote_spanned!(method.span()=> Self::#method(self).await);
+        quote! {
+            async fn on_stop(&mut self) -> ::core::result::Result<(), #error_ty> {
+                #call
+            }
+        }
+    });
+
+    let on_error = args.on_error.as_ref().map(|method| {
+        let call = quote_spanned!(method.span()=> Self::#method(self, err).await);
+        quote! {
+            async fn on_error(&mut self, err: #error_ty) -> ::core::result::Result<(), #error_ty> {
+                #call
+            }
+        }
+    });
+
+    quote! {
+        #on_start
+        #on_stop
+        #on_error
+    }
+}
+
+/// Generates the typed handle named by `#[actor(handle = ...)]`.
+fn expand_handle(
+    input: &DeriveInput,
+    args: &ActorArgs,
+    handle: &Ident,
+    ascolt: &Path,
+) -> TokenStream {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let doc = format!("Typed handle of [`{name}`].");
+
+    let registry = args.name.as_ref().map(|actor_name| {
+        let register_doc = format!(
+            "Registers `sender` as {actor_name:?}, failing if the name is already taken.",
+            actor_name = actor_name.value(),
+        );
+        let lookup_doc = format!(
+            "Looks up the actor registered as {actor_name:?}.",
+            actor_name = actor_name.value(),
+        );
+        quote! {
+            #[doc = #register_doc]
+            #vis fn register(
+                sender: #ascolt::Sender<#name #ty_generics>,
+            ) -> ::core::result::Result<Self, #ascolt::registry::RegistryError> {
+                <#name #ty_generics as #ascolt::registry::NamedActorTrait>::register(
+                    ::core::clone::Clone::clone(&sender),
+                )?;
+                ::core::result::Result::Ok(Self { sender })
+            }
+
+            #[doc = #lookup_doc]
+            #vis fn from_registry() -> ::core::option::Option<Self> {
+                <#name #ty_generics as #ascolt::registry::NamedActorTrait>::lookup()
+                    .map(|sender| Self { sender })
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #doc]
+        #vis struct #handle #generics #where_clause {
+            sender: #ascolt::Sender<#name #ty_generics>,
+        }
+
+        impl #impl_generics ::core::clone::Clone for #handle #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                Self {
+                    sender: ::core::clone::Clone::clone(&self.sender),
+                }
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<#ascolt::Sender<#name #ty_generics>>
+            for #handle #ty_generics #where_clause
+        {
+            fn from(sender: #ascolt::Sender<#name #ty_generics>) -> Self {
+                Self { sender }
+            }
+        }
+
+        impl #impl_generics #handle #ty_generics #where_clause {
+            /// Sends an ask message and waits for the handler's response.
+            #vis async fn ask<M, R, E>(&self, msg: M) -> ::core::result::Result<R, E>
+            where
+                #name #ty_generics: #ascolt::handler::AskHandlerTrait<M, R, E>,
+            {
+                self.sender.ask(msg).await
+            }
+
+            /// Sends a tell message.
+            #vis async fn tell<M, E>(&self, msg: M) -> ::core::result::Result<(), E>
+            where
+                #name #ty_generics: #ascolt::handler::TellHandlerTrait<M, E>,
+            {
+                self.sender.tell(msg).await
+            }
+
+            #registry
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    Attribute, Block, FnArg, Ident, ImplItem, ImplItemFn, ItemImpl, Meta, Type, parse_quote,
+};
+
+use crate::handler::{Handler, HandlerArgs, HandlerKind, impl_handle_method, impl_handler};
+use crate::utils::{Errors, join, merge_generics};
+
+pub(crate) fn expand_actor_handlers(
+    args: TokenStream,
+    mut input: ItemImpl,
+) -> syn::Result<TokenStream> {
+    if let Some((_, path, _)) = &input.trait_ {
+        return Err(syn::Error::new_spanned(
+            path,
+            "#[actor_handlers] expects an inherent impl block",
+        ));
+    }
+
+    let mut args = HandlerArgs::parse(args)?;
+
+    // Priorities and timeouts differ between messages, so they are only given
+    // per method.
+    if let Some(priority) = &args.priority {
+        return Err(syn::Error::new_spanned(
+            priority,
+            "Set the priority per method with #[handler(priority = ...)]",
+        ));
+    }
+    if let Some(timeout_ms) = &args.timeout_ms {
+        return Err(syn::Error::new_spanned(
+            timeout_ms,
+            "Set the timeout per method with #[handler(timeout_ms = ...)]",
+        ));
+    }
+    args.default_error = args.error.take();
+
+    let mut errors = Errors::default();
+    let mut impls = Vec::new();
+
+    for item in &input.items {
+        match item {
+            ImplItem::Fn(method) if is_handler(method) => {
+                if let Some(tokens) = errors.take(expand_method(&args, &input, method)) {
+                    impls.push(tokens);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    errors.finish()?;
+
+    // The impl block is kept so its methods can still be called directly, and
+    // the trait impls call them.
+    for item in &mut input.items {
+        if let ImplItem::Fn(method) = item {
+            strip_markers(method);
+        }
+    }
+
+    Ok(quote! {
+        #input
+        #(#impls)*
+    })
+}
+
+/// Whether a method is a handler: marked as one, or taking `&mut self` and a
+/// message.
+fn is_handler(method: &ImplItemFn) -> bool {
+    let takes_mut_self = method.sig.receiver().is_some_and(
+        |receiver| matches!(receiver.ty.as_ref(), Type::Reference(r) if r.mutability.is_some()),
+    );
+
+    method.attrs.iter().any(is_marker) || (takes_mut_self && method.sig.inputs.len() > 1)
+}
+
+/// Removes the attributes only meaningful to this macro from a method.
+fn strip_markers(method: &mut ImplItemFn) {
+    method.attrs.retain(|attr| !is_marker(attr));
+
+    for input in &mut method.sig.inputs {
+        if let FnArg::Typed(arg) = input {
+            arg.attrs
+                .retain(|attr| !attr.path().is_ident("msg") && !attr.path().is_ident("ctx"));
+        }
+    }
+}
+
+fn expand_method(
+    args: &HandlerArgs,
+    input: &ItemImpl,
+    method: &ImplItemFn,
+) -> syn::Result<TokenStream> {
+    let actor_ty = &input.self_ty;
+
+    let args = &method_args(args, &method.attrs)?;
+    let (marker, mut handler) = join(
+        handler_marker(&method.attrs),
+        Handler::parse(&method.sig, args, Some(actor_ty)),
+    )?;
+
+    // The trait method is always `handle`, and calls the method with the
+    // message and context passed to it.
+    handler.method_name = Ident::new("handle", handler.fn_name.span());
+
+    let msg = Ident::new("msg", Span::mixed_site());
+    let ctx = Ident::new("ctx", Span::mixed_site());
+    handler.msg_pat = parse_quote!(#msg);
+    let call_args = match &mut handler.ctx {
+        Some((pat, _)) => {
+            *pat = parse_quote!(#ctx);
+            if handler.ctx_first {
+                quote!(#ctx, #msg)
+            } else {
+                quote!(#msg, #ctx)
+            }
+        }
+        None => quote!(#msg),
+    };
+    let method_name = &method.sig.ident;
+    let call = quote!(Self::#method_name(self, #call_args));
+    let block: Block = if handler.is_async {
+        parse_quote!({ #call.await })
+    } else {
+        parse_quote!({ #call })
+    };
+
+    let kind = marker.unwrap_or(if handler.is_unit_response() {
+        HandlerKind::Tell
+    } else {
+        HandlerKind::Ask
+    });
+
+    handler.check_kind(kind)?;
+
+    let generics = merge_generics(&input.generics, handler.generics);
+
+    // `#[cfg]`s of the impl block and the method apply to the generated impls,
+    // and the method's docs to its handle method. Other attributes stay on the
+    // method itself, which the trait impl only calls.
+    let attrs: Vec<Attribute> = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .chain(method.attrs.iter().filter(|attr| !is_marker(attr)))
+        .cloned()
+        .collect();
+    let cfgs: Vec<Attribute> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .cloned()
+        .collect();
+
+    let handler_impl = impl_handler(kind, &handler, args, actor_ty, &generics, &cfgs, &block);
+    let handle_method = args
+        .handle
+        .as_ref()
+        .map(|handle_ty| {
+            impl_handle_method(kind, &handler, handle_ty, &generics, &attrs, &method.vis)
+        })
+        .transpose()?;
+
+    Ok(quote! {
+        #handler_impl
+        #handle_method
+    })
+}
+
+fn is_marker(attr: &Attribute) -> bool {
+    ["ask", "tell", "handler"]
+        .iter()
+        .any(|marker| attr.path().is_ident(marker))
+}
+
+/// Options of a method: those of its `#[handler(...)]` attribute, falling
+/// back to the ones of the impl block.
+fn method_args(block: &HandlerArgs, attrs: &[Attribute]) -> syn::Result<HandlerArgs> {
+    let mut args = block.clone();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("handler")) {
+        // A bare `#[handler]` only marks the method.
+        let Meta::List(list) = &attr.meta else {
+            continue;
+        };
+        let HandlerArgs {
+            handle,
+            error,
+            default_error: _,
+            trace,
+            timeout_ms,
+            checked,
+            krate,
+            priority,
+        } = HandlerArgs::parse(list.tokens.clone())?;
+
+        args.handle = handle.or(args.handle);
+        args.error = error.or(args.error);
+        args.trace = trace.or(args.trace);
+        args.timeout_ms = timeout_ms.or(args.timeout_ms);
+        args.checked = checked.or(args.checked);
+        args.krate = krate.or(args.krate);
+        args.priority = priority.or(args.priority);
+    }
+
+    Ok(args)
+}
+
+/// Reads the optional `#[ask]`/`#[tell]` marker of a method.
+fn handler_marker(attrs: &[Attribute]) -> syn::Result<Option<HandlerKind>> {
+    let mut marker = None;
+
+    for attr in attrs {
+        let kind = if attr.path().is_ident("ask") {
+            HandlerKind::Ask
+        } else if attr.path().is_ident("tell") {
+            HandlerKind::Tell
+        } else {
+            continue;
+        };
+
+        attr.meta.require_path_only()?;
+
+        if marker.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "A handler can only have one #[ask] or #[tell] marker",
+            ));
+        }
+        marker = Some(kind);
+    }
+
+    Ok(marker)
+}
@@ -0,0 +1,400 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    GenericParam, Generics, Ident, ItemStruct, Path, Token, Type, parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+};
+
+use crate::actor::ActorArgs;
+use crate::utils::{Errors, message_name};
+
+/// A message listed in `#[actor_messages(...)]`.
+struct MessageEntry {
+    msg_ty: Type,
+    /// Response type of an ask message, `None` for tell messages.
+    resp_ty: Option<Type>,
+    /// Error type of the handler when it differs from the actor's.
+    err_ty: Option<Type>,
+    variant: Ident,
+}
+
+/// `M -> R` or `M -> R | E` in `ask(...)`.
+struct AskEntry {
+    msg_ty: Type,
+    resp_ty: Type,
+    err_ty: Option<Type>,
+}
+
+impl Parse for AskEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let msg_ty = input.parse()?;
+        input.parse::<Token![->]>()?;
+        let resp_ty = input.parse()?;
+        let err_ty = parse_entry_error(input)?;
+
+        Ok(Self {
+            msg_ty,
+            resp_ty,
+            err_ty,
+        })
+    }
+}
+
+/// `M` or `M | E` in `tell(...)`.
+struct TellEntry {
+    msg_ty: Type,
+    err_ty: Option<Type>,
+}
+
+impl Parse for TellEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let msg_ty = input.parse()?;
+        let err_ty = parse_entry_error(input)?;
+
+        Ok(Self { msg_ty, err_ty })
+    }
+}
+
+/// Parses the optional `| E` naming the error type of a message's handler.
+fn parse_entry_error(input: ParseStream) -> syn::Result<Option<Type>> {
+    if input.peek(Token![|]) {
+        input.parse::<Token![|]>()?;
+        Ok(Some(input.parse()?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// A listed message with the response and error types of its handler.
+struct ListedMessage {
+    msg_ty: Type,
+    resp_ty: Option<Type>,
+    err_ty: Option<Type>,
+}
+
+/// Arguments of `#[actor_messages(...)]`.
+struct MessagesArgs {
+    messages: Vec<ListedMessage>,
+    /// Whether to generate `dispatch_remote`.
+    remote: bool,
+    krate: Option<Path>,
+}
+
+fn parse_messages(args: TokenStream) -> syn::Result<MessagesArgs> {
+    let mut messages = Vec::new();
+    let mut remote = false;
+    let mut krate = None;
+
+    let parser = syn::meta::parser(|meta| {
+        let content;
+        if meta.path.is_ident("ask") {
+            parenthesized!(content in meta.input);
+            let entries = Punctuated::<AskEntry, Token![,]>::parse_terminated(&content)?;
+            messages.extend(entries.into_iter().map(|entry| ListedMessage {
+                msg_ty: entry.msg_ty,
+                resp_ty: Some(entry.resp_ty),
+                err_ty: entry.err_ty,
+            }));
+            Ok(())
+        } else if meta.path.is_ident("tell") {
+            parenthesized!(content in meta.input);
+            let entries = Punctuated::<TellEntry, Token![,]>::parse_terminated(&content)?;
+            messages.extend(entries.into_iter().map(|entry| ListedMessage {
+                msg_ty: entry.msg_ty,
+                resp_ty: None,
+                err_ty: entry.err_ty,
+            }));
+            Ok(())
+        } else if meta.path.is_ident("remote") {
+            remote = true;
+            Ok(())
+        } else if meta.path.is_ident("crate") {
+            krate = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported attribute"))
+        }
+    });
+    syn::parse::Parser::parse2(parser, args)?;
+
+    Ok(MessagesArgs {
+        messages,
+        remote,
+        krate,
+    })
+}
+
+/// The handler trait implemented for a listed message, with the handler's
+/// error type defaulting to the actor's.
+fn handler_trait(entry: &MessageEntry, actor_error: &Type, ascolt: &Path) -> TokenStream {
+    let msg_ty = &entry.msg_ty;
+    let err_ty = entry.err_ty.as_ref().unwrap_or(actor_error);
+    match &entry.resp_ty {
+        Some(resp_ty) => quote!(#ascolt::handler::AskHandlerTrait<#msg_ty, #resp_ty, #err_ty>),
+        None => quote!(#ascolt::handler::TellHandlerTrait<#msg_ty, #err_ty>),
+    }
+}
+
+/// Hidden uninhabited variant keeping the actor's generic parameters in use
+/// when no message mentions them.
+fn phantom_variant(generics: &Generics) -> Option<TokenStream> {
+    let params: Vec<TokenStream> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(ty) => {
+                let ident = &ty.ident;
+                Some(quote!(#ident))
+            }
+            GenericParam::Lifetime(lt) => {
+                let lifetime = &lt.lifetime;
+                Some(quote!(&#lifetime ()))
+            }
+            GenericParam::Const(_) => None,
+        })
+        .collect();
+
+    if params.is_empty() {
+        return None;
+    }
+
+    Some(quote! {
+        #[doc(hidden)]
+        __Phantom(
+            ::core::convert::Infallible,
+            ::core::marker::PhantomData<fn() -> (#(#params,)*)>,
+        ),
+    })
+}
+
+pub(crate) fn expand_actor_messages(
+    args: TokenStream,
+    input: ItemStruct,
+) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let vis = &input.vis;
+
+    let actor_args = ActorArgs::parse(&input.attrs)?;
+    let error_ty = actor_args.require_error_ty(name)?;
+
+    let mut errors = Errors::default();
+    let mut entries: Vec<MessageEntry> = Vec::new();
+
+    let MessagesArgs {
+        messages,
+        remote,
+        krate,
+    } = parse_messages(args)?;
+    let ascolt = krate.unwrap_or_else(|| actor_args.ascolt());
+
+    for ListedMessage {
+        msg_ty,
+        resp_ty,
+        err_ty,
+    } in messages
+    {
+        let Some(variant) = errors.take(message_name(&msg_ty)) else {
+            continue;
+        };
+
+        if entries.iter().any(|entry| entry.variant == variant) {
+            errors.push(syn::Error::new_spanned(
+                &msg_ty,
+                format!("Duplicate message variant `{variant}`"),
+            ));
+            continue;
+        }
+
+        entries.push(MessageEntry {
+            msg_ty,
+            resp_ty,
+            err_ty,
+            variant,
+        });
+    }
+
+    errors.finish()?;
+
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let phantom = phantom_variant(generics);
+
+    let msg_enum = format_ident!("{}Msg", name);
+    let reply_enum = format_ident!("{}Reply", name);
+    let phantom_arm = phantom
+        .as_ref()
+        .map(|_| quote!(#msg_enum::__Phantom(never, _) => match never {},));
+
+    let msg_variants = entries.iter().map(
+        |MessageEntry {
+             msg_ty, variant, ..
+         }| { quote!(#variant(#msg_ty)) },
+    );
+
+    let reply_variants = entries.iter().map(|entry| {
+        let variant = &entry.variant;
+        match &entry.resp_ty {
+            Some(resp_ty) => quote!(#variant(#resp_ty)),
+            None => quote!(#variant),
+        }
+    });
+
+    let from_impls = entries.iter().map(
+        |MessageEntry {
+             msg_ty, variant, ..
+         }| {
+            quote! {
+                impl #impl_generics ::core::convert::From<#msg_ty> for #msg_enum #ty_generics #where_clause {
+                    fn from(msg: #msg_ty) -> Self {
+                        #msg_enum::#variant(msg)
+                    }
+                }
+            }
+        },
+    );
+
+    // Messages are handled with the caller's context when ascolt's handler
+    // traits take one.
+    let (ctx_param, ctx) = if cfg!(feature = "context") {
+        (
+            Some(quote!(ctx: &mut #ascolt::Context<Self>,)),
+            Some(quote!(ctx)),
+        )
+    } else {
+        (None, None)
+    };
+
+    // Handler errors other than the actor's are converted into it.
+    let arms = entries.iter().map(|entry| {
+        let variant = &entry.variant;
+        let handler_trait = handler_trait(entry, error_ty, &ascolt);
+        let handled = quote!(<Self as #handler_trait>::handle(self, msg, #ctx).await);
+        let reply = match &entry.resp_ty {
+            Some(_) => quote!(#reply_enum::#variant),
+            None => quote!(|()| #reply_enum::#variant),
+        };
+        quote! {
+            #msg_enum::#variant(msg) => #handled
+                .map(#reply)
+                .map_err(::core::convert::From::from),
+        }
+    });
+
+    let dispatch_remote = remote.then(|| {
+        let remote_arms = entries.iter().map(|entry| {
+            let msg_ty = &entry.msg_ty;
+            let handler_trait = handler_trait(entry, error_ty, &ascolt);
+            quote! {
+                if tag == <#msg_ty as #ascolt::remote::RemoteMessageTrait>::TAG {
+                    let msg = <#msg_ty as #ascolt::remote::RemoteMessageTrait>::decode(bytes)?;
+                    let result = <Self as #handler_trait>::handle(self, msg, #ctx).await;
+                    return #ascolt::remote::encode(&result);
+                }
+            }
+        });
+
+        // An actor with two messages under one tag would only ever route to
+        // the first of them. The tags are compared while type checking, and
+        // `Tags<true>` lacking the trait below reports the pair.
+        let tag_checks: Vec<TokenStream> = entries
+            .iter()
+            .enumerate()
+            .flat_map(|(i, first)| {
+                let ascolt = &ascolt;
+                entries[i + 1..].iter().map(move |second| {
+                    let (first_ty, second_ty) = (&first.msg_ty, &second.msg_ty);
+                    let message = format!(
+                        "`{}` and `{}` have the same remote tag",
+                        first.variant, second.variant,
+                    );
+                    quote_spanned! {second_ty.span()=>
+                        {
+                            #[diagnostic::on_unimplemented(
+                                message = #message,
+                                label = "listed under the tag of an earlier message",
+                            )]
+                            trait DistinctTags {}
+                            impl DistinctTags for Tags<false> {}
+                            fn distinct_tags<T: DistinctTags>() {}
+                            distinct_tags::<Tags<{
+                                same_tag(
+                                    <#first_ty as #ascolt::remote::RemoteMessageTrait>::TAG,
+                                    <#second_ty as #ascolt::remote::RemoteMessageTrait>::TAG,
+                                )
+                            }>>();
+                        }
+                    }
+                })
+            })
+            .collect();
+        let tag_check = (!tag_checks.is_empty()).then(|| {
+            quote! {
+                struct Tags<const SAME: bool>;
+                const fn same_tag(a: &str, b: &str) -> bool {
+                    let (a, b) = (a.as_bytes(), b.as_bytes());
+                    if a.len() != b.len() {
+                        return false;
+                    }
+                    let mut i = 0;
+                    while i < a.len() {
+                        if a[i] != b[i] {
+                            return false;
+                        }
+                        i += 1;
+                    }
+                    true
+                }
+                #(#tag_checks)*
+            }
+        });
+
+        quote! {
+            /// Decodes a message received under its `RemoteMessage` tag,
+            /// routes it to its handler and encodes the handler's result.
+            #vis async fn dispatch_remote(
+                &mut self,
+                tag: &str,
+                bytes: &[u8],
+                #ctx_param
+            ) -> ::core::result::Result<::std::vec::Vec<u8>, #ascolt::remote::CodecError> {
+                #tag_check
+                #(#remote_arms)*
+                ::core::result::Result::Err(#ascolt::remote::CodecError::unknown_tag(tag))
+            }
+        }
+    });
+
+    Ok(quote! {
+        #input
+
+        #vis enum #msg_enum #generics #where_clause {
+            #(#msg_variants,)*
+            #phantom
+        }
+
+        #vis enum #reply_enum #generics #where_clause {
+            #(#reply_variants,)*
+            #phantom
+        }
+
+        #(#from_impls)*
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Routes a message to the handler registered for its type.
+            #vis async fn dispatch(
+                &mut self,
+                msg: #msg_enum #ty_generics,
+                #ctx_param
+            ) -> ::core::result::Result<#reply_enum #ty_generics, #error_ty> {
+                match msg {
+                    #(#arms)*
+                    #phantom_arm
+                }
+            }
+
+            #dispatch_remote
+        }
+    })
+}
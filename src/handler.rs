@@ -0,0 +1,810 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, quote, quote_spanned};
+use syn::{
+    Attribute, Block, FnArg, GenericArgument, Generics, Ident, ItemFn, LitInt, LitStr, Pat,
+    PatType, Path, PathArguments, PathSegment, ReturnType, Signature, Type, Visibility,
+    parse_quote, spanned::Spanned, token,
+};
+
+use crate::utils::{
+    Errors, ascolt_path, async_trait_attr, join, message_name, parse_priority, parse_quotable,
+    respan, snake_case, strip_reference, support_path,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HandlerKind {
+    Ask,
+    Tell,
+}
+
+/// Parts of a handler signature needed to generate its trait impl.
+pub(crate) struct Handler<'a> {
+    pub(crate) fn_name: &'a Ident,
+    /// Name of the generated trait method, the handler's own name unless the
+    /// handler is a method of another type.
+    pub(crate) method_name: Ident,
+    pub(crate) generics: &'a Generics,
+    pub(crate) actor_ty: Box<Type>,
+    /// Pattern the message is bound to, e.g. `msg` or `GetUser { id }`.
+    pub(crate) msg_pat: Box<Pat>,
+    pub(crate) msg_ty: Box<Type>,
+    /// Pattern and type of the `ctx` argument, if the handler takes one.
+    pub(crate) ctx: Option<(Box<Pat>, Box<Type>)>,
+    /// Whether the `ctx` argument comes before the message.
+    pub(crate) ctx_first: bool,
+    pub(crate) resp_ty: Type,
+    pub(crate) err_ty: Type,
+    /// Whether the error type was given rather than taken from the actor.
+    pub(crate) explicit_error: bool,
+    /// Whether the handler is an `async fn`.
+    pub(crate) is_async: bool,
+    /// Whether the handler returns a `Result` itself rather than having its
+    /// result wrapped in `Ok`.
+    pub(crate) fallible: bool,
+}
+
+impl<'a> Handler<'a> {
+    /// Parses a handler of the actor `self_ty`, or of the actor taken by its
+    /// receiver when `self_ty` is `None`.
+    pub(crate) fn parse(
+        sig: &'a Signature,
+        args: &HandlerArgs,
+        self_ty: Option<&Type>,
+    ) -> syn::Result<Self> {
+        let (inputs, (resp_ty, err_ty, fallible)) =
+            join(extract_handler_args(sig), extract_result_types(sig, args))?;
+
+        if let Some(timeout_ms) = args.timeout_ms.as_ref().filter(|_| sig.asyncness.is_none()) {
+            return Err(syn::Error::new_spanned(
+                timeout_ms,
+                "timeout_ms requires an async handler",
+            ));
+        }
+
+        let explicit_error = err_ty.is_some();
+        // Without ascolt's `ActorErrorTrait` the actor's error cannot be named
+        // here, so only handlers that never fail go without an error type.
+        let err_ty = match err_ty {
+            Some(err_ty) => err_ty,
+            None if cfg!(feature = "actor-error") => {
+                let actor_ty = self_ty.unwrap_or_else(|| strip_reference(&inputs.actor_ty));
+                let ascolt = args.ascolt();
+                parse_quote!(<#actor_ty as #ascolt::ActorErrorTrait>::Error)
+            }
+            None if fallible => {
+                return Err(syn::Error::new_spanned(
+                    &sig.output,
+                    "Missing error type of the Result alias, give it as error = ...",
+                ));
+            }
+            None => parse_quote!(::core::convert::Infallible),
+        };
+
+        let position = |arg: &PatType| {
+            sig.inputs
+                .iter()
+                .position(|input| matches!(input, FnArg::Typed(typed) if std::ptr::eq(typed, arg)))
+        };
+        let ctx_first = inputs
+            .ctx
+            .is_some_and(|ctx| position(ctx) < position(inputs.msg));
+
+        Ok(Self {
+            fn_name: &sig.ident,
+            method_name: sig.ident.clone(),
+            generics: &sig.generics,
+            actor_ty: inputs.actor_ty,
+            msg_pat: inputs.msg.pat.clone(),
+            msg_ty: inputs.msg.ty.clone(),
+            ctx: inputs.ctx.map(|ctx| (ctx.pat.clone(), ctx.ty.clone())),
+            ctx_first,
+            resp_ty,
+            err_ty,
+            explicit_error,
+            fallible,
+            is_async: sig.asyncness.is_some(),
+        })
+    }
+
+    /// Checks that the return type suits a handler of `kind`.
+    pub(crate) fn check_kind(&self, kind: HandlerKind) -> syn::Result<()> {
+        if kind == HandlerKind::Tell && !self.is_unit_response() {
+            return Err(syn::Error::new_spanned(
+                &self.resp_ty,
+                "Tell handlers cannot respond with a value, expected Result<(), E>",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the handler returns `Result<(), E>`.
+    pub(crate) fn is_unit_response(&self) -> bool {
+        matches!(&self.resp_ty, Type::Tuple(tuple) if tuple.elems.is_empty())
+    }
+}
+
+/// Options of the `#[ask_handler(...)]`/`#[tell_handler(...)]` attributes.
+#[derive(Clone, Default)]
+pub(crate) struct HandlerArgs {
+    /// Actor handle type that gets a typed method calling this handler.
+    pub(crate) handle: Option<Type>,
+    /// Error type of handlers returning a single-parameter alias such as
+    /// `anyhow::Result<T>`.
+    pub(crate) error: Option<Type>,
+    /// Like `error`, but given for a whole `#[actor_handlers]` block and so
+    /// only used by methods whose return type does not name an error.
+    pub(crate) default_error: Option<Type>,
+    /// Level of the `tracing` span wrapping the handler, from `trace` or
+    /// `trace(level = "debug")`.
+    pub(crate) trace: Option<Ident>,
+    /// Time limit of async handlers in milliseconds.
+    pub(crate) timeout_ms: Option<LitInt>,
+    /// Requires the response and error types to match the message's
+    /// `#[derive(Message)]` declaration.
+    pub(crate) checked: Option<Ident>,
+    /// Path of the ascolt crate given as `crate = ...`.
+    pub(crate) krate: Option<Path>,
+    /// Mailbox priority of the handled message.
+    pub(crate) priority: Option<Ident>,
+}
+
+impl HandlerArgs {
+    pub(crate) fn parse(args: TokenStream) -> syn::Result<Self> {
+        let mut handler_args = Self::default();
+
+        let parser = syn::meta::parser(|meta| {
+            if meta.path.is_ident("handle") {
+                handler_args.handle = Some(parse_quotable(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("error") {
+                handler_args.error = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("trace") {
+                let mut level = Ident::new("INFO", meta.path.span());
+                if meta.input.peek(token::Paren) {
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("level") {
+                            level = parse_trace_level(meta.value()?.parse()?)?;
+                            Ok(())
+                        } else {
+                            Err(meta.error("unsupported trace option"))
+                        }
+                    })?;
+                }
+                handler_args.trace = Some(level);
+                Ok(())
+            } else if meta.path.is_ident("timeout_ms") {
+                let timeout_ms: LitInt = meta.value()?.parse()?;
+                timeout_ms.base10_parse::<u64>()?;
+                handler_args.timeout_ms = Some(timeout_ms);
+                Ok(())
+            } else if meta.path.is_ident("checked") {
+                handler_args.checked = meta.path.get_ident().cloned();
+                Ok(())
+            } else if meta.path.is_ident("crate") {
+                handler_args.krate = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("priority") {
+                handler_args.priority = Some(parse_priority(&meta)?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported attribute"))
+            }
+        });
+        syn::parse::Parser::parse2(parser, args)?;
+
+        Ok(handler_args)
+    }
+
+    /// Path of the ascolt crate, from `crate = ...` or the calling crate's
+    /// dependencies.
+    pub(crate) fn ascolt(&self) -> Path {
+        ascolt_path(self.krate.as_ref())
+    }
+}
+
+fn parse_trace_level(level: LitStr) -> syn::Result<Ident> {
+    match level.value().as_str() {
+        "trace" | "debug" | "info" | "warn" | "error" => {
+            Ok(Ident::new(&level.value().to_uppercase(), level.span()))
+        }
+        _ => Err(syn::Error::new_spanned(
+            level,
+            "Expected one of \"trace\", \"debug\", \"info\", \"warn\", \"error\"",
+        )),
+    }
+}
+
+pub(crate) fn expand_ask_handler(args: TokenStream, input: ItemFn) -> syn::Result<TokenStream> {
+    expand_handler(HandlerKind::Ask, args, input)
+}
+
+pub(crate) fn expand_tell_handler(args: TokenStream, input: ItemFn) -> syn::Result<TokenStream> {
+    expand_handler(HandlerKind::Tell, args, input)
+}
+
+fn expand_handler(kind: HandlerKind, args: TokenStream, input: ItemFn) -> syn::Result<TokenStream> {
+    let args = HandlerArgs::parse(args)?;
+    let handler = Handler::parse(&input.sig, &args, None)?;
+    handler.check_kind(kind)?;
+    let clean_actor_ty = strip_reference(&handler.actor_ty);
+
+    let handler_impl = impl_handler(
+        kind,
+        &handler,
+        &args,
+        clean_actor_ty,
+        handler.generics,
+        &input.attrs,
+        &input.block,
+    );
+    let handle_method = args
+        .handle
+        .as_ref()
+        .map(|handle_ty| {
+            impl_handle_method(
+                kind,
+                &handler,
+                handle_ty,
+                handler.generics,
+                &input.attrs,
+                &input.vis,
+            )
+        })
+        .transpose()?;
+
+    Ok(quote! {
+        #handler_impl
+        #handle_method
+    })
+}
+
+/// Emits the `AskHandlerTrait`/`TellHandlerTrait` impl of `handler` for `actor_ty`.
+///
+/// The handler's own generic parameters are expected to be part of `generics`,
+/// as trait methods cannot introduce parameters the trait does not declare.
+/// `#[cfg]` attributes of the handler apply to the whole impl, any other
+/// attributes are kept on the generated method.
+pub(crate) fn impl_handler(
+    kind: HandlerKind,
+    handler: &Handler,
+    args: &HandlerArgs,
+    actor_ty: &Type,
+    generics: &Generics,
+    attrs: &[Attribute],
+    block: &Block,
+) -> TokenStream {
+    let Handler {
+        method_name,
+        actor_ty: self_ty,
+        msg_pat,
+        msg_ty,
+        ctx,
+        resp_ty,
+        err_ty,
+        explicit_error,
+        fallible,
+        is_async,
+        ..
+    } = handler;
+
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let clean_msg_ty = strip_reference(msg_ty);
+    let stmts = &block.stmts;
+    let ascolt = args.ascolt();
+
+    let async_trait = async_trait_attr(&ascolt);
+    let (cfgs, method_attrs): (Vec<_>, Vec<_>) =
+        attrs.iter().partition(|attr| attr.path().is_ident("cfg"));
+
+    // Trait methods only take a context when ascolt's handler traits do.
+    let ctx_arg = match ctx {
+        Some((pat, ty)) => Some(quote!(#pat: #ty)),
+        None if cfg!(feature = "context") => Some(quote!(_: &mut #ascolt::Context<Self>)),
+        None => None,
+    };
+
+    // Destructuring patterns are rebound from a plain argument so the trait
+    // method keeps a simple signature.
+    let (msg_arg, msg_binding) = match msg_pat.as_ref() {
+        Pat::Ident(pat_ident) => (quote!(#pat_ident), None),
+        pat => {
+            let arg = Ident::new("__msg", Span::mixed_site());
+            (quote!(#arg), Some(quote!(let #pat = #arg;)))
+        }
+    };
+
+    let (trait_path, output) = match kind {
+        HandlerKind::Ask => (
+            quote!(#ascolt::handler::AskHandlerTrait<#clean_msg_ty, #resp_ty, #err_ty>),
+            quote!(::core::result::Result<#resp_ty, #err_ty>),
+        ),
+        HandlerKind::Tell => (
+            quote!(#ascolt::handler::TellHandlerTrait<#clean_msg_ty, #err_ty>),
+            quote!(::core::result::Result<(), #err_ty>),
+        ),
+    };
+
+    let trace = |run: TokenStream, sync: bool| {
+        let run = measure_handler(handler, &ascolt, actor_ty, &output, run, sync);
+        trace_handler(args, handler, actor_ty, &output, run, sync)
+    };
+
+    // Without `async_trait`, synchronous handlers run when called and hand back
+    // an already completed future instead of an async state machine.
+    let method = if cfg!(feature = "native-async") && !is_async {
+        let result = if *fallible {
+            quote!((|| -> #output { #(#stmts)* })())
+        } else {
+            quote!(::core::result::Result::Ok((|| -> #resp_ty { #(#stmts)* })()))
+        };
+        let result = trace(result, true);
+        quote! {
+            fn #method_name(
+                self: #self_ty,
+                #msg_arg: #msg_ty,
+                #ctx_arg
+            ) -> impl ::core::future::Future<Output = #output> + ::core::marker::Send {
+                #msg_binding
+                ::core::future::ready(#result)
+            }
+        }
+    } else {
+        let body = if *fallible {
+            quote!(#(#stmts)*)
+        } else {
+            quote!(::core::result::Result::Ok(async { #(#stmts)* }.await))
+        };
+        let body = trace(timeout_handler(args, body), false);
+        quote! {
+            async fn #method_name(
+                self: #self_ty,
+                #msg_arg: #msg_ty,
+                #ctx_arg
+            ) -> #output {
+                #msg_binding
+                #body
+            }
+        }
+    };
+
+    // The handler's own priority wins over the one declared by its message,
+    // which is only known to implement `MessageTrait` for checked handlers.
+    let priority = match (&args.priority, &args.checked) {
+        (Some(priority), _) => Some(quote!(#ascolt::mailbox::Priority::#priority)),
+        (None, Some(_)) => Some(quote!(<#clean_msg_ty as #ascolt::MessageTrait>::PRIORITY)),
+        (None, None) => None,
+    }
+    .map(|priority| quote!(const PRIORITY: #ascolt::mailbox::Priority = #priority;));
+
+    let trait_impl = quote! {
+        #(#cfgs)*
+        #async_trait
+        impl #impl_generics #trait_path for #actor_ty #where_clause {
+            #priority
+
+            #(#method_attrs)*
+            #method
+        }
+    };
+
+    let mut assertions = Vec::new();
+
+    // Reported at the handler's error type as `ActorError: From<HandlerError>`
+    // not being satisfied, naming both types. `Infallible` handlers never
+    // produce an error to convert, and without the `actor-error` feature the
+    // actor's error cannot be named.
+    if cfg!(feature = "actor-error") && *explicit_error && !is_infallible(err_ty) {
+        let actor_ty = respan(actor_ty.to_token_stream(), err_ty.span());
+        assertions.push(quote_spanned! {err_ty.span()=>
+            fn handler_error_converts_into_actor_error<A, E>()
+            where
+                A: #ascolt::ActorErrorTrait,
+                <A as #ascolt::ActorErrorTrait>::Error: ::core::convert::From<E>,
+            {
+            }
+            handler_error_converts_into_actor_error::<#actor_ty, #err_ty>();
+        });
+    }
+
+    // Reported at `checked` when the handler's response or error type differs
+    // from the one declared by `#[derive(Message)]`.
+    if let Some(checked) = &args.checked {
+        let msg_ty = respan(clean_msg_ty.to_token_stream(), checked.span());
+        let resp_ty = respan(resp_ty.to_token_stream(), checked.span());
+        let err_ty = respan(err_ty.to_token_stream(), checked.span());
+        assertions.push(quote_spanned! {checked.span()=>
+            fn handler_matches_message<M, R, E>()
+            where
+                M: #ascolt::MessageTrait<Response = R, Error = E>,
+            {
+            }
+            handler_matches_message::<#msg_ty, #resp_ty, #err_ty>();
+        });
+    }
+
+    let checks = (!assertions.is_empty()).then(|| {
+        quote! {
+            #(#cfgs)*
+            const _: () = {
+                #[allow(dead_code)]
+                fn check #impl_generics () #where_clause {
+                    #(#assertions)*
+                }
+            };
+        }
+    });
+
+    quote! {
+        #trait_impl
+        #checks
+    }
+}
+
+/// Races a handler body against `timeout_ms`, failing with the handler's
+/// error converted from `ascolt::TimeoutError` once it elapses.
+fn timeout_handler(args: &HandlerArgs, body: TokenStream) -> TokenStream {
+    let Some(timeout_ms) = &args.timeout_ms else {
+        return body;
+    };
+
+    let ascolt = args.ascolt();
+    // The timeout is converted through a function spanned at `timeout_ms`, so
+    // an error type lacking `From<TimeoutError>` is reported there.
+    let from_timeout = quote_spanned! {timeout_ms.span()=>
+        fn handler_error_from_timeout<E>(elapsed: #ascolt::TimeoutError) -> E
+        where
+            E: ::core::convert::From<#ascolt::TimeoutError>,
+        {
+            ::core::convert::From::from(elapsed)
+        }
+    };
+    let elapsed = quote_spanned!(timeout_ms.span()=> handler_error_from_timeout(elapsed));
+
+    quote! {{
+        #from_timeout
+        match #ascolt::timeout(
+            ::core::time::Duration::from_millis(#timeout_ms),
+            async { #body },
+        )
+        .await
+        {
+            ::core::result::Result::Ok(result) => result,
+            ::core::result::Result::Err(elapsed) => ::core::result::Result::Err(#elapsed),
+        }
+    }}
+}
+
+/// Runs a handler body inside a `tracing` span naming the actor, message and
+/// handler, recording the error if it fails. `run` is an expression for
+/// synchronous handlers and the statements of the body otherwise, and the
+/// result is a block expression.
+///
+/// Leaves `run` as is unless `trace` was requested and the `tracing` feature
+/// is enabled.
+fn trace_handler(
+    args: &HandlerArgs,
+    handler: &Handler,
+    actor_ty: &Type,
+    output: &TokenStream,
+    run: TokenStream,
+    sync: bool,
+) -> TokenStream {
+    let Some(level) = args.trace.as_ref().filter(|_| cfg!(feature = "tracing")) else {
+        return run;
+    };
+
+    let tracing = support_path("tracing", &args.ascolt());
+    let msg_ty = strip_reference(&handler.msg_ty);
+    let fn_name = handler.fn_name.to_string();
+    let span = Ident::new("__span", Span::mixed_site());
+    let result = Ident::new("__result", Span::mixed_site());
+
+    let run = if sync {
+        quote!(#span.in_scope(|| #run))
+    } else {
+        quote! {
+            #tracing::Instrument::instrument(
+                async { #run },
+                ::core::clone::Clone::clone(&#span),
+            )
+            .await
+        }
+    };
+
+    // The error is recorded through a function requiring `Debug`, spanned at
+    // `trace` so an error type lacking it is reported there rather than in
+    // `tracing`.
+    let debug = quote_spanned! {level.span()=>
+        fn handler_error_is_debug<E: ::core::fmt::Debug>(
+            err: &E,
+        ) -> #tracing::field::DebugValue<&E> {
+            #tracing::field::debug(err)
+        }
+    };
+    let record = quote_spanned!(level.span()=> handler_error_is_debug(err));
+
+    quote! {{
+        #debug
+        let #span = #tracing::span!(
+            #tracing::Level::#level,
+            "handler",
+            actor = ::core::any::type_name::<#actor_ty>(),
+            message = ::core::any::type_name::<#msg_ty>(),
+            handler = #fn_name,
+            error = #tracing::field::Empty,
+        );
+        let #result: #output = #run;
+        if let ::core::result::Result::Err(err) = &#result {
+            #span.record("error", #record);
+        }
+        #result
+    }}
+}
+
+/// Records the call count, error count and duration of a handler through the
+/// `metrics` facade, labelled with the actor and message types. `run` is
+/// taken as in [`trace_handler`] and the result is a block expression.
+///
+/// Leaves `run` as is unless the `metrics` feature is enabled.
+fn measure_handler(
+    handler: &Handler,
+    ascolt: &Path,
+    actor_ty: &Type,
+    output: &TokenStream,
+    run: TokenStream,
+    sync: bool,
+) -> TokenStream {
+    if !cfg!(feature = "metrics") {
+        return run;
+    }
+
+    let metrics = support_path("metrics", ascolt);
+    let msg_ty = strip_reference(&handler.msg_ty);
+    let start = Ident::new("__start", Span::mixed_site());
+    let result = Ident::new("__result", Span::mixed_site());
+
+    let run = if sync {
+        run
+    } else {
+        quote!(async { #run }.await)
+    };
+    let labels = quote! {
+        "actor" => ::core::any::type_name::<#actor_ty>(),
+        "message" => ::core::any::type_name::<#msg_ty>(),
+    };
+
+    quote! {{
+        let #start = ::std::time::Instant::now();
+        let #result: #output = #run;
+        #metrics::counter!("ascolt_handler_calls_total", #labels).increment(1);
+        if #result.is_err() {
+            #metrics::counter!("ascolt_handler_errors_total", #labels).increment(1);
+        }
+        #metrics::histogram!("ascolt_handler_duration_seconds", #labels)
+            .record(#start.elapsed().as_secs_f64());
+        #result
+    }}
+}
+
+fn is_infallible(ty: &Type) -> bool {
+    match ty {
+        Type::Path(tp) => tp
+            .path
+            .segments
+            .last()
+            .is_some_and(|seg| seg.ident == "Infallible"),
+        _ => false,
+    }
+}
+
+/// Arguments of a handler signature.
+struct HandlerInputs<'a> {
+    actor_ty: Box<Type>,
+    msg: &'a PatType,
+    ctx: Option<&'a PatType>,
+}
+
+/// Finds the `self` receiver, the message and the optional context argument of
+/// a handler, reporting every problem at once.
+///
+/// The context is the argument marked `#[ctx]` or named `ctx`. The message is
+/// the argument marked `#[msg]`, else the one named `msg`, else the only
+/// remaining argument.
+fn extract_handler_args(sig: &Signature) -> syn::Result<HandlerInputs<'_>> {
+    let mut errors = Errors::default();
+    let mut actor_ty = None;
+    let mut typed = Vec::new();
+
+    for arg in &sig.inputs {
+        match arg {
+            FnArg::Receiver(receiver) => actor_ty = Some(receiver.ty.clone()),
+            FnArg::Typed(pat_type) => typed.push(pat_type),
+        }
+    }
+
+    let args_span = sig.paren_token.span.join();
+    let actor_ty =
+        actor_ty.ok_or_else(|| syn::Error::new(args_span, "Missing self: &Actor argument"));
+
+    let ctx = errors.take(find_arg(&typed, "ctx")).flatten();
+    if let Some(ctx) = ctx.filter(|_| !cfg!(feature = "context")) {
+        errors.push(syn::Error::new_spanned(
+            ctx,
+            "ctx arguments require the `context` feature of ascolt",
+        ));
+    }
+    typed.retain(|arg| !ctx.is_some_and(|ctx| std::ptr::eq(*arg, ctx)));
+
+    let only_arg = match typed.as_slice() {
+        [arg] => Some(*arg),
+        _ => None,
+    };
+    let msg = find_arg(&typed, "msg").and_then(|msg| {
+        msg.or(only_arg).ok_or_else(|| {
+            let message = if typed.is_empty() {
+                "Missing msg argument"
+            } else {
+                "Ambiguous message argument, mark it with #[msg]"
+            };
+            syn::Error::new(args_span, message)
+        })
+    });
+
+    if let Ok(msg) = msg {
+        for arg in typed.iter().filter(|arg| !std::ptr::eq(**arg, msg)) {
+            errors.push(syn::Error::new_spanned(arg, "Unexpected handler argument"));
+        }
+    }
+
+    let ((actor_ty, msg), ()) = join(join(actor_ty, msg), errors.finish())?;
+
+    Ok(HandlerInputs { actor_ty, msg, ctx })
+}
+
+/// Finds the argument marked `#[<name>]`, else the one bound to `name`.
+fn find_arg<'a>(args: &[&'a PatType], name: &str) -> syn::Result<Option<&'a PatType>> {
+    let mut marked = None;
+    let mut named = None;
+
+    for &arg in args {
+        if let Some(attr) = arg.attrs.iter().find(|a| a.path().is_ident(name)) {
+            if marked.is_some() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    format!("Only one argument can be marked #[{name}]"),
+                ));
+            }
+            marked = Some(arg);
+        }
+        if let Pat::Ident(pat_ident) = arg.pat.as_ref() {
+            if pat_ident.ident == name {
+                named = Some(arg);
+            }
+        }
+    }
+
+    Ok(marked.or(named))
+}
+
+/// Extracts `T` and `E` from a `Result<T, E>` return type, accepting any path
+/// whose last segment is `Result`, and whether the handler returns a `Result`
+/// at all. Other return types are infallible responses, and handlers without
+/// a return type respond with `()`.
+///
+/// Single-parameter aliases such as `anyhow::Result<T>` take their error type
+/// from the `error` option or the default error of an `#[actor_handlers]`
+/// block. When neither is given, they fail with the actor's declared error
+/// under the `actor-error` feature and are rejected without it.
+/// Aliases with other names ending in `Result`, e.g. `DbResult<T>`, are only
+/// taken as results when the handler's own `error` is given, as
+/// `SearchResult` or `QueryResult<Row>` may just as well be response types.
+fn extract_result_types(
+    sig: &Signature,
+    args: &HandlerArgs,
+) -> syn::Result<(Type, Option<Type>, bool)> {
+    let error = args.error.as_ref();
+    let given_error = error.or(args.default_error.as_ref());
+
+    match &sig.output {
+        ReturnType::Type(_, ty) => {
+            let seg = match ty.as_ref() {
+                Type::Path(tp) => tp.path.segments.last(),
+                _ => None,
+            };
+            let is_result = |seg: &&PathSegment| {
+                seg.ident == "Result"
+                    || (error.is_some() && seg.ident.to_string().ends_with("Result"))
+            };
+            let Some(seg) = seg.filter(is_result) else {
+                return Ok((ty.as_ref().clone(), given_error.cloned(), false));
+            };
+
+            let args = match &seg.arguments {
+                PathArguments::AngleBracketed(args) => args,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        seg,
+                        "Expected Result<T, E> with angle-bracketed args",
+                    ));
+                }
+            };
+
+            match (args.args.iter().collect::<Vec<_>>().as_slice(), error) {
+                ([resp, err], None) => {
+                    let (resp, err) = join(generic_type(resp), generic_type(err))?;
+                    Ok((resp, Some(err), true))
+                }
+                ([_, _], Some(error)) => Err(syn::Error::new_spanned(
+                    error,
+                    "The error type is already given by the return type",
+                )),
+                ([resp], _) => Ok((generic_type(resp)?, given_error.cloned(), true)),
+                ([], _) => Err(syn::Error::new_spanned(
+                    args,
+                    "Missing success type in Result<T, E>",
+                )),
+                _ => Err(syn::Error::new_spanned(
+                    args,
+                    "Expected Result<T> or Result<T, E>",
+                )),
+            }
+        }
+        ReturnType::Default => Ok((parse_quote!(()), given_error.cloned(), false)),
+    }
+}
+
+fn generic_type(arg: &GenericArgument) -> syn::Result<Type> {
+    match arg {
+        GenericArgument::Type(ty) => Ok(ty.clone()),
+        _ => Err(syn::Error::new_spanned(arg, "Expected a type")),
+    }
+}
+
+/// Emits a typed method on an actor handle type forwarding to the handle's
+/// generic `ask`/`tell`, named after the message type in snake case. It keeps
+/// the handler's docs and `#[cfg]` attributes.
+pub(crate) fn impl_handle_method(
+    kind: HandlerKind,
+    handler: &Handler,
+    handle_ty: &Type,
+    generics: &Generics,
+    attrs: &[Attribute],
+    vis: &Visibility,
+) -> syn::Result<TokenStream> {
+    let Handler {
+        msg_ty,
+        resp_ty,
+        err_ty,
+        ..
+    } = handler;
+
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let clean_msg_ty = strip_reference(msg_ty);
+    let method_name = snake_case(&message_name(clean_msg_ty)?)?;
+
+    let method = match kind {
+        HandlerKind::Ask => quote! {
+            #vis async fn #method_name(&self, msg: #clean_msg_ty) -> ::core::result::Result<#resp_ty, #err_ty> {
+                self.ask::<#clean_msg_ty, #resp_ty, #err_ty>(msg).await
+            }
+        },
+        HandlerKind::Tell => quote! {
+            #vis async fn #method_name(&self, msg: #clean_msg_ty) -> ::core::result::Result<(), #err_ty> {
+                self.tell::<#clean_msg_ty, #err_ty>(msg).await
+            }
+        },
+    };
+
+    let cfgs = attrs.iter().filter(|attr| attr.path().is_ident("cfg"));
+    let docs = attrs.iter().filter(|attr| attr.path().is_ident("doc"));
+
+    Ok(quote! {
+        #(#cfgs)*
+        impl #impl_generics #handle_ty #where_clause {
+            #(#docs)*
+            #method
+        }
+    })
+}
@@ -1,181 +1,288 @@
-use proc_macro::TokenStream;
-use quote::quote;
-use syn::{
-    DeriveInput, FnArg, ItemFn, Pat, PatType, PathArguments, ReturnType, Type, parse_macro_input,
-};
+//! Macros for the [ascolt](https://github.com/sterrlia/ascolt) actor framework.
+//!
+//! Generated impls use `#[async_trait::async_trait]` by default. With the
+//! `native-async` feature they use `async fn` in traits instead, which avoids
+//! boxing a future per message and the dependency on `async-trait`. The impls
+//! must be written the way ascolt declares its traits, so the feature is not
+//! additive: it is enabled by ascolt's own `native-async` feature, and must not
+//! be enabled on ascolt-macros directly.
+//!
+//! Handlers may also be plain `fn`s. With `native-async` their generated trait
+//! methods run the body on call and return `core::future::Ready`, skipping the
+//! async state machine.
+//!
+//! The `context` feature makes every generated trait method, and the
+//! `dispatch` methods of `#[actor_messages]`, take a `&mut ascolt::Context<Self>`
+//! after the message. It matches ascolt versions whose handler traits take a
+//! context, which enable it through ascolt's own `context` feature.
+//!
+//! The `actor-error` feature makes `#[derive(Actor)]` implement
+//! `ascolt::ActorErrorTrait` naming the actor's error type. Handlers whose
+//! return type names no error then fail with the actor's error, and the
+//! compiler checks that the error of every other handler converts into it with
+//! `From`. Without the feature such handlers need an `error = ...` option,
+//! unless they cannot fail, in which case their error is `Infallible`. ascolt
+//! versions providing `ActorErrorTrait` enable it through their own
+//! `actor-error` feature.
+//!
+//! With the `tracing` feature, handlers marked `trace` or
+//! `trace(level = "debug")` run inside a `tracing` span recording the actor,
+//! message and handler names and the error of a failed call. The error is
+//! recorded with its `Debug` output, so traced handlers need an error type
+//! implementing `Debug`, which is checked at the `trace` option. Without the
+//! feature the option is accepted and generates nothing.
+//!
+//! The `metrics` feature makes every handler record
+//! `ascolt_handler_calls_total`, `ascolt_handler_errors_total` and
+//! `ascolt_handler_duration_seconds` through the `metrics` crate, labelled with
+//! the `actor` and `message` type names.
+//!
+//! Generated code refers to ascolt under the name it is imported as in the
+//! calling crate's `Cargo.toml`, so renamed dependencies work. When ascolt is
+//! reached through another crate, pass its path with the `crate = ...` option
+//! accepted by every macro, e.g. `#[ask_handler(crate = my_facade::ascolt)]`
+//! or `#[actor(error = MyError, crate = my_facade::ascolt)]`.
+//!
+//! async-trait, serde, `tracing` and `metrics` are likewise taken from the
+//! calling crate's dependencies when it has them, and otherwise from their
+//! re-exports in ascolt's path, e.g. `my_facade::ascolt::async_trait`.
 
+use proc_macro::TokenStream;
+use syn::{DeriveInput, ItemFn, ItemImpl, ItemStruct, parse_macro_input};
+
+mod actor;
+mod actor_handlers;
+mod actor_messages;
+mod handler;
+mod message;
+mod supervisor;
+mod utils;
+
+/// Implements `AskHandlerTrait` for the actor taken by `self`.
+///
+/// The message is the argument marked `#[msg]`, else the one named `msg`, else
+/// the only other argument. It may be a destructuring pattern such as
+/// `GetUser { id }: GetUser`.
+///
+/// An optional argument named `ctx` or marked `#[ctx]` receives the actor's
+/// `&mut ascolt::Context<Self>`. This needs the `context` feature described in
+/// the [crate docs](crate); without it `ctx` arguments are rejected.
+///
+/// The return type may be any path to a type named `Result`, such as
+/// `std::result::Result<T, E>`. For single-parameter aliases like
+/// `anyhow::Result<T>` the error type is `#[ask_handler(error = E)]`, or with
+/// the `actor-error` feature the actor's `#[actor(error = ...)]` when not
+/// given. Aliases under other names, such as `DbResult<T>`, are only read as
+/// results when `error` is given; otherwise types like `SearchResult` are
+/// plain responses.
+///
+/// Any other return type makes an infallible handler whose value is wrapped
+/// in `Ok`, with the error type chosen the same way, defaulting to
+/// `Infallible` without the `actor-error` feature.
+///
+/// `#[ask_handler(handle = MyActorRef)]` also adds a method named after the
+/// message in snake case (`get_user` for `GetUser`) to the actor's generated
+/// handle type. Names that are keywords are raw identifiers, e.g. `r#move` for
+/// `Move`.
+///
+/// `#[ask_handler(timeout_ms = 500)]` fails async handlers running longer
+/// than the limit with an error converted from `ascolt::TimeoutError`.
+///
+/// `#[ask_handler(checked)]` asserts that the response and error types are the
+/// ones declared by the message's `#[derive(Message)]`.
+///
+/// `#[ask_handler(priority = high)]` sets the mailbox priority of the message
+/// for this actor to `low`, `normal` or `high`. Priority mailboxes handle
+/// higher priority messages before queued lower priority ones. Without the
+/// option, handlers marked `checked` take the priority declared by the
+/// message's `#[derive(Message)]`, and other handlers are `normal`.
 #[proc_macro_attribute]
-pub fn ask_handler(_args: TokenStream, item: TokenStream) -> TokenStream {
+pub fn ask_handler(args: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemFn);
 
-    let sig = &input.sig;
-    let block = &input.block;
-
-    let fn_name = &sig.ident;
-    let inputs = &sig.inputs;
-    let output = &sig.output;
-
-    let mut actor_ty = None;
-    let mut msg_ty = None;
-
-    for arg in inputs {
-        match arg {
-            FnArg::Receiver(receiver) => actor_ty = Some(receiver.ty.clone()),
-            FnArg::Typed(PatType { pat, ty, .. }) => {
-                if let Pat::Ident(pat_ident) = pat.as_ref() {
-                    let ident = pat_ident.ident.to_string();
-                    if ident.as_str() == "msg" {
-                        msg_ty = Some(ty.clone())
-                    }
-                }
-            }
-        }
-    }
-
-    let actor_ty = actor_ty.expect("Missing self: &Actor argument");
-    let msg_ty = msg_ty.expect("Missing msg argument");
-
-    let clean_actor_ty = strip_reference(&actor_ty);
-    let clean_msg_ty = strip_reference(&msg_ty);
-
-    let (resp_ty, err_ty) = extract_result_types(output);
-
-    let expanded = quote! {
-        #[async_trait::async_trait]
-        impl ascolt::handler::AskHandlerTrait<#clean_msg_ty, #resp_ty, #err_ty> for #clean_actor_ty {
-            async fn #fn_name(
-                self: #actor_ty,
-                msg: #msg_ty,
-            ) -> Result<#resp_ty, #err_ty> {
-                #block
-            }
-        }
-    };
-
-    TokenStream::from(expanded)
+    handler::expand_ask_handler(args.into(), input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
+/// Implements `TellHandlerTrait` for the actor taken by `self`.
+///
+/// Besides `Result<(), E>`, tell handlers may have no return type, in which
+/// case the error type is `#[tell_handler(error = E)]`, else the actor's own
+/// with the `actor-error` feature and `Infallible` without it.
+///
+/// Accepts the same `handle = MyActorRef`, `timeout_ms`, `checked` and
+/// `priority` options as `#[ask_handler]`.
 #[proc_macro_attribute]
-pub fn tell_handler(_args: TokenStream, item: TokenStream) -> TokenStream {
+pub fn tell_handler(args: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemFn);
 
-    let sig = &input.sig;
-    let block = &input.block;
-
-    let fn_name = &sig.ident;
-    let inputs = &sig.inputs;
-    let output = &sig.output;
-
-    let mut actor_ty = None;
-    let mut msg_ty = None;
-
-    for arg in inputs {
-        match arg {
-            FnArg::Receiver(receiver) => actor_ty = Some(receiver.ty.clone()),
-            FnArg::Typed(PatType { pat, ty, .. }) => {
-                if let Pat::Ident(pat_ident) = pat.as_ref() {
-                    let ident = pat_ident.ident.to_string();
-                    if ident.as_str() == "msg" {
-                        msg_ty = Some(ty.clone())
-                    }
-                }
-            }
-        }
-    }
-
-    let actor_ty = actor_ty.expect("Missing self: &Actor argument");
-    let msg_ty = msg_ty.expect("Missing msg argument");
-
-    let clean_actor_ty = strip_reference(&actor_ty);
-    let clean_msg_ty = strip_reference(&msg_ty);
-
-    let (_, err_ty) = extract_result_types(output);
-
-    let expanded = quote! {
-        #[async_trait::async_trait]
-        impl ascolt::handler::TellHandlerTrait<#clean_msg_ty, #err_ty> for #clean_actor_ty {
-            async fn #fn_name(
-                self: #actor_ty,
-                msg: #msg_ty,
-            ) -> Result<(), #err_ty> {
-                #block
-            }
-        }
-    };
-
-    TokenStream::from(expanded)
+    handler::expand_tell_handler(args.into(), input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
-fn strip_reference(ty: &syn::Type) -> &syn::Type {
-    match ty {
-        syn::Type::Reference(r) => strip_reference(&r.elem),
-        _ => ty,
-    }
+/// Generates a handler impl for every handler method of an inherent `impl`
+/// block.
+///
+/// Methods taking `&mut self` and a message are handlers, as are methods
+/// marked `#[ask]`, `#[tell]` or `#[handler]`. Other items, such as
+/// constructors or `&self` getters, are left as they are. The block is kept
+/// with its methods, which can still be called directly, and the generated
+/// `handle` of each handler calls its method.
+///
+/// Methods returning `Result<(), E>` or nothing become tell handlers, any
+/// other return type makes an ask handler. Mark a method with `#[ask]` or
+/// `#[tell]` to choose explicitly. `#[actor_handlers(handle = MyActorRef)]`
+/// adds a method per handler to the actor's generated handle type.
+///
+/// Options of the block apply to every method. A method's own
+/// `#[handler(...)]` attribute takes the options of `#[ask_handler]` and
+/// overrides those of the block; `priority` and `timeout_ms` are only accepted
+/// there. The block's `error` is only used by methods whose return type does
+/// not name an error, and unlike a method's own `error` does not make aliases
+/// such as `DbResult<T>` read as results:
+///
+/// ```ignore
+/// #[actor_handlers(error = anyhow::Error)]
+/// impl MyActor {
+///     #[handler(priority = high, timeout_ms = 500)]
+///     async fn get_user(&mut self, msg: GetUser) -> anyhow::Result<User> { ... }
+///
+///     fn set_user(&mut self, msg: SetUser) -> Result<(), DbError> { ... }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn actor_handlers(args: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as ItemImpl);
+
+    actor_handlers::expand_actor_handlers(args.into(), input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
-fn extract_result_types(
-    output: &ReturnType,
-) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
-    match output {
-        ReturnType::Type(_, ty) => {
-            let type_path = match ty.as_ref() {
-                Type::Path(tp) => tp,
-                _ => panic!("Expected a path type (e.g. Result<T, E>)"),
-            };
-
-            let seg = type_path
-                .path
-                .segments
-                .first()
-                .expect("Expected a Result return type");
-
-            if seg.ident != "Result" {
-                panic!("Return type must be Result<T, E>");
-            }
-
-            let args = match &seg.arguments {
-                PathArguments::AngleBracketed(args) => args,
-                _ => panic!("Expected Result<T, E> with angle-bracketed args"),
-            };
-
-            let mut args_iter = args.args.iter();
-            let resp = args_iter
-                .next()
-                .expect("Missing success type in Result<T, E>");
-            let err = args_iter
-                .next()
-                .expect("Missing error type in Result<T, E>");
-
-            (quote!(#resp), quote!(#err))
-        }
-        _ => panic!("Expected function to have a return type"),
-    }
+/// Generates a `<Actor>Msg` enum over the listed messages, a matching
+/// `<Actor>Reply` enum and a `dispatch` method routing each message to its
+/// handler impl.
+///
+/// Place it above `#[derive(Actor)]` so it can read `#[actor(error = ...)]`:
+///
+/// ```ignore
+/// #[actor_messages(ask(GetUser -> User, Search -> Vec<User> | DbError), tell(SetUser))]
+/// #[derive(Actor)]
+/// #[actor(error = MyError)]
+/// struct MyActor;
+/// ```
+///
+/// Attribute macros cannot see other items, so the handlers are not collected
+/// from the actor's impls: every message must be listed here again, with the
+/// response type of ask messages. Handlers whose error type is not the actor's
+/// name it after a `|`, and `dispatch` converts it into the actor's error with
+/// `From`.
+///
+/// With `remote` listed among the arguments, a `dispatch_remote` method also
+/// decodes messages deriving `RemoteMessage` by their tag and encodes the
+/// handler's result. Listing two messages with the same tag fails to compile.
+#[proc_macro_attribute]
+pub fn actor_messages(args: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as ItemStruct);
+
+    actor_messages::expand_actor_messages(args.into(), input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
+/// Implements `ActorTrait` for the error type given in `#[actor(error = ...)]`,
+/// and `ActorErrorTrait` with the `actor-error` feature.
+///
+/// `#[actor(handle = MyActorRef)]`, also written `handle = "MyActorRef"`,
+/// additionally generates a cloneable handle wrapping `ascolt::Sender` with
+/// generic `ask`/`tell` methods. Typed methods such as `get_user` are only
+/// added for handlers given the same `handle = MyActorRef` option; other
+/// handlers are reached through `ask` and `tell`.
+///
+/// `on_start = "init"`, `on_stop = "shutdown"` and `on_error = "handle_err"`
+/// forward the corresponding `ActorTrait` hooks to async methods of the actor:
+/// `init`/`shutdown` take `&mut self`, `handle_err` additionally takes the
+/// error, and all of them return `Result<(), E>`.
+///
+/// `name = "billing"` implements `NamedActorTrait` so the actor can be
+/// registered in and looked up from ascolt's process-wide registry. The handle
+/// then also gets `register(sender)`, which fails if the name is already
+/// taken, and `from_registry()`.
+///
+/// `mailbox = bounded(1024)` or `mailbox = unbounded` sets the
+/// `ActorTrait::MAILBOX` configuration used when spawning the actor. Bounded
+/// mailboxes take an `overflow` policy of `"drop_oldest"`, `"drop_newest"`,
+/// `"block"` or `"error"`.
 #[proc_macro_derive(Actor, attributes(actor))]
 pub fn derive_actor(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
-
-    let mut error_ty = None;
-    for attr in input.attrs.iter().filter(|a| a.path().is_ident("actor")) {
-        attr.parse_nested_meta(|meta| {
-            if meta.path.is_ident("error") {
-                let value: syn::Type = meta.value()?.parse()?;
-                error_ty = Some(value);
-                Ok(())
-            } else {
-                Err(meta.error("unsupported attribute"))
-            }
-        })
-        .unwrap();
-    }
-
-    let error_ty = error_ty.expect("missing #[actor(error = ...)]");
-
-    let expanded = quote! {
-        impl ascolt::ActorTrait<#error_ty> for #name {}
-    };
-
-    TokenStream::from(expanded)
+
+    actor::expand_derive_actor(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `MessageTrait` with the types given in
+/// `#[message(response = User, error = DbError)]`.
+///
+/// The response defaults to `()`, as answered by tell handlers. Handlers marked
+/// `checked` must return exactly these types. `priority = high` sets the
+/// priority of the message for those handlers, unless they set their own.
+#[proc_macro_derive(Message, attributes(message))]
+pub fn derive_message(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    message::expand_derive_message(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `RemoteMessageTrait`, encoding the message with serde under a
+/// stable tag so it can be sent to another process. The message must
+/// implement serde's `Serialize` and `DeserializeOwned`, usually derived.
+///
+/// The tag is the type name unless given as `#[remote(tag = "billing.get_user")]`.
+/// Tags are looked up per actor rather than in a process-wide registry:
+/// `#[actor_messages(..., remote)]` generates a `dispatch_remote` method
+/// taking the tag and bytes received from any transport, decoding them into
+/// the listed message with that tag and encoding its handler's result.
+#[proc_macro_derive(RemoteMessage, attributes(remote))]
+pub fn derive_remote_message(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    message::expand_derive_remote_message(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `SupervisorTrait`, describing the fields marked `#[child]` as
+/// actors to spawn, monitor and restart.
+///
+/// ```ignore
+/// #[derive(Supervisor)]
+/// #[supervisor(strategy = "one_for_all", max_restarts = 3, within_ms = 10_000)]
+/// struct Services {
+///     #[child(restart = "permanent")]
+///     db: Db,
+///     #[child(restart = "transient")]
+///     mailer: Mailer,
+/// }
+/// ```
+///
+/// Each child field holds the initial state of an actor, cloned whenever it is
+/// (re)started. Children are started in field order, which `rest_for_one`
+/// relies on. `restart` is `"permanent"`, `"transient"` or `"temporary"` and
+/// defaults to `"permanent"`. `strategy` is `"one_for_one"` (the default),
+/// `"one_for_all"` or `"rest_for_one"`, and may also be written on a child.
+/// More than `max_restarts` restarts within `within_ms` milliseconds stop the
+/// supervisor, by default 1 in 5000.
+#[proc_macro_derive(Supervisor, attributes(supervisor, child))]
+pub fn derive_supervisor(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    supervisor::expand_derive_supervisor(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
@@ -0,0 +1,128 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{Attribute, DeriveInput, Ident, LitStr, Path, Type, parse_quote};
+
+use crate::utils::{ascolt_path, parse_priority, support_path};
+
+/// Options of the `#[message(...)]` attribute.
+#[derive(Default)]
+pub(crate) struct MessageArgs {
+    pub(crate) response: Option<Type>,
+    pub(crate) error: Option<Type>,
+    pub(crate) krate: Option<Path>,
+    pub(crate) priority: Option<Ident>,
+}
+
+impl MessageArgs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut args = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("message")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("response") {
+                    args.response = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("error") {
+                    args.error = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("crate") {
+                    args.krate = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("priority") {
+                    args.priority = Some(parse_priority(&meta)?);
+                } else {
+                    return Err(meta.error("unsupported attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(args)
+    }
+
+    pub(crate) fn require_error(&self, name: &Ident) -> syn::Result<&Type> {
+        self.error
+            .as_ref()
+            .ok_or_else(|| syn::Error::new_spanned(name, "missing #[message(error = ...)]"))
+    }
+}
+
+pub(crate) fn expand_derive_message(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+
+    let args = MessageArgs::parse(&input.attrs)?;
+    let error = args.require_error(name)?;
+    let response = args.response.clone().unwrap_or_else(|| parse_quote!(()));
+    let ascolt = ascolt_path(args.krate.as_ref());
+    let priority = args.priority.as_ref().map(|priority| {
+        quote!(const PRIORITY: #ascolt::mailbox::Priority = #ascolt::mailbox::Priority::#priority;)
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #ascolt::MessageTrait for #name #ty_generics #where_clause {
+            type Response = #response;
+            type Error = #error;
+            #priority
+        }
+    })
+}
+
+/// Reads the wire tag from `#[remote(tag = "...")]`, defaulting to the type
+/// name, and the `crate = ...` option.
+fn parse_remote_args(input: &DeriveInput) -> syn::Result<(LitStr, Option<Path>)> {
+    let mut tag = None;
+    let mut krate = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("remote")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                tag = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("crate") {
+                krate = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unsupported attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let tag = tag.unwrap_or_else(|| LitStr::new(&input.ident.to_string(), input.ident.span()));
+
+    Ok((tag, krate))
+}
+
+pub(crate) fn expand_derive_remote_message(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+
+    // A tag names exactly one type on the wire.
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "Remote messages cannot be generic",
+        ));
+    }
+
+    let (tag, krate) = parse_remote_args(&input)?;
+    let ascolt = ascolt_path(krate.as_ref());
+
+    // ascolt encodes messages with serde. A message that cannot be serialized
+    // is reported at its name rather than inside the generated methods.
+    let serde = support_path("serde", &ascolt);
+    let serde_bound = quote_spanned! {name.span()=>
+        #name: #serde::Serialize + #serde::de::DeserializeOwned
+    };
+
+    Ok(quote! {
+        impl #ascolt::remote::RemoteMessageTrait for #name
+        where
+            #serde_bound,
+        {
+            const TAG: &'static str = #tag;
+
+            fn encode(&self) -> ::core::result::Result<::std::vec::Vec<u8>, #ascolt::remote::CodecError> {
+                #ascolt::remote::encode(self)
+            }
+
+            fn decode(bytes: &[u8]) -> ::core::result::Result<Self, #ascolt::remote::CodecError> {
+                #ascolt::remote::decode(bytes)
+            }
+        }
+    })
+}
@@ -0,0 +1,206 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{
+    Attribute, Data, DeriveInput, Field, Ident, LitInt, LitStr, Member, Meta, Path,
+    meta::ParseNestedMeta, spanned::Spanned,
+};
+
+use crate::utils::{Errors, ascolt_path, parse_variant};
+
+const STRATEGIES: &[&str] = &["one_for_one", "one_for_all", "rest_for_one"];
+const RESTARTS: &[&str] = &["permanent", "transient", "temporary"];
+
+/// Options of the `#[supervisor(...)]` attribute.
+#[derive(Default)]
+struct SupervisorArgs {
+    strategy: Option<Ident>,
+    max_restarts: Option<LitInt>,
+    within_ms: Option<LitInt>,
+    krate: Option<Path>,
+}
+
+impl SupervisorArgs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut args = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("supervisor")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("strategy") {
+                    args.set_strategy(&meta)?;
+                } else if meta.path.is_ident("max_restarts") {
+                    args.max_restarts = Some(parse_int::<u32>(&meta)?);
+                } else if meta.path.is_ident("within_ms") {
+                    args.within_ms = Some(parse_int::<u64>(&meta)?);
+                } else if meta.path.is_ident("crate") {
+                    args.krate = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unsupported attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(args)
+    }
+
+    /// Sets the strategy, which may be given on the supervisor or on any of
+    /// its children as long as they all agree.
+    fn set_strategy(&mut self, meta: &ParseNestedMeta) -> syn::Result<()> {
+        let value: LitStr = meta.value()?.parse()?;
+        let strategy = parse_variant(&value, STRATEGIES)?;
+
+        match &self.strategy {
+            Some(existing) if *existing != strategy => Err(syn::Error::new_spanned(
+                value,
+                "Conflicting supervision strategies",
+            )),
+            _ => {
+                self.strategy = Some(strategy);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn parse_int<N>(meta: &ParseNestedMeta) -> syn::Result<LitInt>
+where
+    N: std::str::FromStr,
+    N::Err: std::fmt::Display,
+{
+    let value: LitInt = meta.value()?.parse()?;
+    value.base10_parse::<N>()?;
+    Ok(value)
+}
+
+/// A field marked `#[child(...)]`.
+struct Child {
+    member: Member,
+    span: Span,
+    name: String,
+    restart: Ident,
+}
+
+/// Reads the `#[child(...)]` options of a field, returning `None` for fields
+/// that are not children.
+fn parse_child(
+    args: &mut SupervisorArgs,
+    member: Member,
+    field: &Field,
+) -> syn::Result<Option<Child>> {
+    let mut restart = None;
+    let mut is_child = false;
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("child")) {
+        is_child = true;
+        if matches!(attr.meta, Meta::Path(_)) {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("restart") {
+                let value: LitStr = meta.value()?.parse()?;
+                restart = Some(parse_variant(&value, RESTARTS)?);
+            } else if meta.path.is_ident("strategy") {
+                args.set_strategy(&meta)?;
+            } else {
+                return Err(meta.error("unsupported attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    if !is_child {
+        return Ok(None);
+    }
+
+    let name = match &member {
+        Member::Named(ident) => ident.to_string(),
+        Member::Unnamed(index) => index.index.to_string(),
+    };
+    let restart = restart.unwrap_or_else(|| Ident::new("Permanent", Span::call_site()));
+
+    Ok(Some(Child {
+        member,
+        span: field.ty.span(),
+        name,
+        restart,
+    }))
+}
+
+pub(crate) fn expand_derive_supervisor(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "Supervisor can only be derived for structs",
+        ));
+    };
+
+    let mut args = SupervisorArgs::parse(&input.attrs)?;
+
+    let mut errors = Errors::default();
+    let mut children = Vec::new();
+
+    for (index, field) in data.fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        if let Some(Some(child)) = errors.take(parse_child(&mut args, member, field)) {
+            children.push(child);
+        }
+    }
+
+    errors.finish()?;
+
+    if children.is_empty() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "A supervisor needs at least one #[child] field",
+        ));
+    }
+
+    let ascolt = ascolt_path(args.krate.as_ref());
+    let strategy = args
+        .strategy
+        .unwrap_or_else(|| Ident::new("OneForOne", Span::call_site()));
+    let max_restarts = args
+        .max_restarts
+        .map_or_else(|| quote!(1), |max_restarts| quote!(#max_restarts));
+    let within_ms = args
+        .within_ms
+        .map_or_else(|| quote!(5000), |within_ms| quote!(#within_ms));
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // Spanned to the field type, where a child that is not a cloneable actor
+    // is reported.
+    let child_specs = children.iter().map(
+        |Child {
+             member,
+             span,
+             name,
+             restart,
+         }| {
+            quote_spanned! {*span=>
+                .child(#ascolt::supervisor::ChildSpec::new(
+                    #name,
+                    #ascolt::supervisor::Restart::#restart,
+                    ::core::clone::Clone::clone(&self.#member),
+                ))
+            }
+        },
+    );
+
+    Ok(quote! {
+        impl #impl_generics #ascolt::supervisor::SupervisorTrait for #name #ty_generics #where_clause {
+            fn supervisor_spec(&self) -> #ascolt::supervisor::SupervisorSpec {
+                #ascolt::supervisor::SupervisorSpec::new(
+                    #ascolt::supervisor::Strategy::#strategy,
+                    #max_restarts,
+                    ::core::time::Duration::from_millis(#within_ms),
+                )
+                #(#child_specs)*
+            }
+        }
+    })
+}
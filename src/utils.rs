@@ -0,0 +1,261 @@
+use proc_macro_crate::{FoundCrate, crate_name};
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::parse::Parse;
+use syn::{GenericParam, Generics, Ident, LitStr, Path, Type, WherePredicate, parse_quote};
+
+/// `#[async_trait]` for generated impls of ascolt's async traits, resolved
+/// like [`support_path`]. With the `native-async` feature the impls use
+/// `async fn` in traits directly.
+pub(crate) fn async_trait_attr(ascolt: &Path) -> TokenStream {
+    if cfg!(feature = "native-async") {
+        TokenStream::new()
+    } else {
+        let async_trait = support_path("async-trait", ascolt);
+        quote!(#[#async_trait::async_trait])
+    }
+}
+
+/// Path of the ascolt crate in generated code: the `crate = ...` option when
+/// given, else the name ascolt is imported under by the calling crate.
+pub(crate) fn ascolt_path(krate: Option<&Path>) -> Path {
+    krate
+        .cloned()
+        .unwrap_or_else(|| find_dependency("ascolt").unwrap_or_else(|| parse_quote!(::ascolt)))
+}
+
+/// Path of a crate the generated code needs besides ascolt, such as
+/// `tracing`: the calling crate's own dependency when it has one, else the
+/// re-export from ascolt (e.g. `ascolt::tracing`).
+pub(crate) fn support_path(package: &str, ascolt: &Path) -> Path {
+    find_dependency(package).unwrap_or_else(|| {
+        let ident = Ident::new(&package.replace('-', "_"), Span::call_site());
+        parse_quote!(#ascolt::#ident)
+    })
+}
+
+/// Absolute path of a dependency of the calling crate, following renames in
+/// its Cargo.toml.
+fn find_dependency(package: &str) -> Option<Path> {
+    match crate_name(package).ok()? {
+        FoundCrate::Itself => Some(parse_quote!(crate)),
+        FoundCrate::Name(name) => {
+            let ident = Ident::new(&name, Span::call_site());
+            Some(parse_quote!(::#ident))
+        }
+    }
+}
+
+pub(crate) fn strip_reference(ty: &Type) -> &Type {
+    match ty {
+        Type::Reference(r) => strip_reference(&r.elem),
+        _ => ty,
+    }
+}
+
+/// Names a message after the last segment of its type path, e.g. `GetUser`
+/// for `api::GetUser<Id>`.
+pub(crate) fn message_name(ty: &Type) -> syn::Result<Ident> {
+    match strip_reference(ty) {
+        Type::Path(tp) if tp.qself.is_none() => tp
+            .path
+            .segments
+            .last()
+            .map(|seg| seg.ident.clone())
+            .ok_or_else(|| syn::Error::new_spanned(ty, "Expected a message type")),
+        _ => Err(syn::Error::new_spanned(
+            ty,
+            "Expected a message type path (e.g. GetUser)",
+        )),
+    }
+}
+
+/// Parses `key = value`, also accepting the value as a string literal as in
+/// `key = "value"`.
+pub(crate) fn parse_quotable<T: Parse>(meta: &ParseNestedMeta) -> syn::Result<T> {
+    let input = meta.value()?;
+    if input.peek(LitStr) {
+        input.parse::<LitStr>()?.parse()
+    } else {
+        input.parse()
+    }
+}
+
+/// Keywords usable as raw identifiers, e.g. `r#move`.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Maps a string option to the variant named after it, e.g. `"one_for_one"` to
+/// `OneForOne`, keeping the span of the literal.
+pub(crate) fn parse_variant(value: &LitStr, choices: &[&str]) -> syn::Result<Ident> {
+    let name = value.value();
+    if !choices.contains(&name.as_str()) {
+        let expected: Vec<String> = choices
+            .iter()
+            .map(|choice| format!("\"{choice}\""))
+            .collect();
+        return Err(syn::Error::new_spanned(
+            value,
+            format!("Expected one of {}", expected.join(", ")),
+        ));
+    }
+
+    let variant: String = name
+        .split('_')
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars))
+                .into_iter()
+                .flatten()
+        })
+        .collect();
+
+    Ok(Ident::new(&variant, value.span()))
+}
+
+/// Parses `priority = high` into the matching `Priority` variant.
+pub(crate) fn parse_priority(meta: &ParseNestedMeta) -> syn::Result<Ident> {
+    let level: Ident = meta.value()?.parse()?;
+    let variant = match level.to_string().as_str() {
+        "low" => "Low",
+        "normal" => "Normal",
+        "high" => "High",
+        _ => {
+            return Err(syn::Error::new_spanned(
+                level,
+                "Expected one of low, normal, high",
+            ));
+        }
+    };
+
+    Ok(Ident::new(variant, level.span()))
+}
+
+/// Converts a `CamelCase` identifier to `snake_case`, e.g. `GetHTTPUser` to
+/// `get_http_user`. Keywords become raw identifiers, and the path keywords
+/// that cannot be raw (`self`, `super`, `crate`) are an error.
+pub(crate) fn snake_case(ident: &Ident) -> syn::Result<Ident> {
+    let name = ident.to_string();
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev_lower =
+                i > 0 && (chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit());
+            let acronym_end = i > 0
+                && chars[i - 1].is_uppercase()
+                && chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if prev_lower || acronym_end {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+
+    match snake.as_str() {
+        "self" | "super" | "crate" => Err(syn::Error::new_spanned(
+            ident,
+            format!("`{snake}` cannot be used as a method name"),
+        )),
+        _ if KEYWORDS.contains(&snake.as_str()) => Ok(Ident::new_raw(&snake, Span::call_site())),
+        _ => Ok(Ident::new(&snake, Span::call_site())),
+    }
+}
+
+/// Moves every token of `tokens` to `span`, so that errors about them are
+/// reported there.
+pub(crate) fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            if let TokenTree::Group(group) = &token {
+                let mut respanned = Group::new(group.delimiter(), respan(group.stream(), span));
+                respanned.set_span(span);
+                token = TokenTree::Group(respanned);
+            }
+            token.set_span(span);
+            token
+        })
+        .collect()
+}
+
+/// Combines two results, keeping the errors of both when both failed.
+pub(crate) fn join<A, B>(a: syn::Result<A>, b: syn::Result<B>) -> syn::Result<(A, B)> {
+    match (a, b) {
+        (Ok(a), Ok(b)) => Ok((a, b)),
+        (Err(mut a), Err(b)) => {
+            a.combine(b);
+            Err(a)
+        }
+        (Err(err), Ok(_)) | (Ok(_), Err(err)) => Err(err),
+    }
+}
+
+/// Collects errors from independent checks so they are reported together.
+#[derive(Default)]
+pub(crate) struct Errors(Option<syn::Error>);
+
+impl Errors {
+    pub(crate) fn push(&mut self, err: syn::Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(err),
+            None => self.0 = Some(err),
+        }
+    }
+
+    pub(crate) fn take<T>(&mut self, result: syn::Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.push(err);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> syn::Result<()> {
+        match self.0 {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Merges the generics of an outer item (e.g. an `impl` block) with those of
+/// an inner one, keeping lifetimes ahead of type and const parameters.
+pub(crate) fn merge_generics(outer: &Generics, inner: &Generics) -> Generics {
+    let (lifetimes, others): (Vec<_>, Vec<_>) = outer
+        .params
+        .iter()
+        .chain(&inner.params)
+        .cloned()
+        .partition(|param| matches!(param, GenericParam::Lifetime(_)));
+
+    let predicates: Vec<WherePredicate> = outer
+        .where_clause
+        .iter()
+        .chain(&inner.where_clause)
+        .flat_map(|clause| clause.predicates.iter().cloned())
+        .collect();
+
+    let mut generics = Generics {
+        params: lifetimes.into_iter().chain(others).collect(),
+        ..Generics::default()
+    };
+    if !predicates.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+
+    generics
+}
@@ -0,0 +1,4 @@
+//! Tests of the code generated by ascolt-macros, compiled against the stub
+//! ascolt in `tests/stub`. The crate depends on none of async-trait, `tracing`
+//! and `metrics`, so the generated code reaches them through ascolt's
+//! re-exports.
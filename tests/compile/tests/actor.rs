@@ -0,0 +1,142 @@
+use ascolt::mailbox::{MailboxConfig, Overflow};
+use ascolt::registry::{NamedActorTrait, RegistryError};
+use ascolt::{ActorTrait, Sender, block_on};
+use ascolt_macros::{Actor, ask_handler};
+
+#[derive(Debug, PartialEq)]
+enum ConnError {
+    Refused,
+    Lost,
+}
+
+#[derive(Default, Actor)]
+#[actor(
+    error = ConnError,
+    handle = "ConnRef",
+    on_start = "connect",
+    on_stop = "disconnect",
+    on_error = "reconnect"
+)]
+struct Conn {
+    log: Vec<&'static str>,
+}
+
+impl Conn {
+    async fn connect(&mut self) -> Result<(), ConnError> {
+        self.log.push("connect");
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnError> {
+        self.log.push("disconnect");
+        Ok(())
+    }
+
+    async fn reconnect(&mut self, err: ConnError) -> Result<(), ConnError> {
+        self.log.push("reconnect");
+        match err {
+            ConnError::Lost => Ok(()),
+            ConnError::Refused => Err(err),
+        }
+    }
+}
+
+struct Ping;
+
+#[ask_handler(error = ConnError, handle = ConnRef)]
+fn handle(self: &mut Conn, _msg: Ping) -> &'static str {
+    "pong"
+}
+
+#[test]
+fn hooks_call_the_named_methods() {
+    block_on(async {
+        let mut conn = Conn::default();
+
+        assert_eq!(ActorTrait::on_start(&mut conn).await, Ok(()));
+        assert_eq!(
+            ActorTrait::on_error(&mut conn, ConnError::Lost).await,
+            Ok(())
+        );
+        assert_eq!(
+            ActorTrait::on_error(&mut conn, ConnError::Refused).await,
+            Err(ConnError::Refused)
+        );
+        assert_eq!(ActorTrait::on_stop(&mut conn).await, Ok(()));
+
+        assert_eq!(
+            conn.log,
+            ["connect", "reconnect", "reconnect", "disconnect"]
+        );
+    });
+}
+
+#[test]
+fn handle_named_by_string() {
+    block_on(async {
+        let conn = ConnRef::from(Sender::new(Conn::default()));
+        assert_eq!(conn.clone().ping(Ping).await, Ok("pong"));
+    });
+}
+
+#[derive(Actor)]
+#[actor(error = ConnError)]
+struct Plain;
+
+#[test]
+fn hooks_default_without_options() {
+    block_on(async {
+        assert_eq!(ActorTrait::on_start(&mut Plain).await, Ok(()));
+        assert_eq!(
+            ActorTrait::on_error(&mut Plain, ConnError::Lost).await,
+            Err(ConnError::Lost)
+        );
+        assert_eq!(Plain::MAILBOX, MailboxConfig::unbounded());
+    });
+}
+
+#[derive(Actor)]
+#[actor(error = ConnError, name = "billing", handle = BillingRef)]
+struct Billing;
+
+#[derive(Actor)]
+#[actor(error = ConnError, name = "audit", handle = AuditRef)]
+struct Audit;
+
+#[test]
+fn named_actors_register_under_their_name() {
+    assert_eq!(Billing::NAME, "billing");
+    assert!(AuditRef::from_registry().is_none());
+
+    let sender = Sender::new(Billing);
+    let billing = BillingRef::register(sender.clone());
+    assert!(billing.is_ok());
+    assert!(BillingRef::register(Sender::new(Billing)).is_err_and(|err| err == RegistryError));
+
+    let found = Billing::lookup().expect("billing is registered");
+    assert!(found.same_actor(&sender));
+    assert!(BillingRef::from_registry().is_some());
+    assert!(AuditRef::from_registry().is_none());
+}
+
+#[derive(Actor)]
+#[actor(error = ConnError, mailbox = bounded(64))]
+struct Bounded;
+
+#[derive(Actor)]
+#[actor(error = ConnError, mailbox = bounded(8), overflow = "drop_oldest")]
+struct Lossy;
+
+#[derive(Actor)]
+#[actor(error = ConnError, mailbox = unbounded)]
+struct Unbounded;
+
+#[test]
+fn mailbox_options_set_the_config() {
+    assert_eq!(Bounded::MAILBOX, MailboxConfig::bounded(64));
+    assert_eq!(
+        Lossy::MAILBOX,
+        MailboxConfig::bounded(8).with_overflow(Overflow::DropOldest)
+    );
+    assert_eq!(Unbounded::MAILBOX, MailboxConfig::unbounded());
+}
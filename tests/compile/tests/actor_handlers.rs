@@ -0,0 +1,179 @@
+use ascolt::Sender;
+use ascolt::block_on;
+use ascolt::handler::{AskHandlerTrait, TellHandlerTrait};
+use ascolt::mailbox::Priority;
+use ascolt_macros::{Actor, actor_handlers};
+
+#[derive(Debug, PartialEq)]
+struct CounterError;
+
+#[derive(Default, Actor)]
+#[actor(error = CounterError, handle = CounterRef)]
+struct Counter {
+    count: u32,
+}
+
+// Error of handlers that cannot fail, the actor's when ascolt can name it.
+#[cfg(feature = "actor-error")]
+type InfallibleError = CounterError;
+#[cfg(not(feature = "actor-error"))]
+type InfallibleError = std::convert::Infallible;
+
+struct Add(u32);
+
+struct Get;
+
+struct Reset;
+
+struct Move(u32);
+
+#[actor_handlers(handle = CounterRef, trace)]
+impl Counter {
+    const LIMIT: u32 = 100;
+
+    fn starting_at(count: u32) -> Self {
+        Self { count }
+    }
+
+    fn count(&self) -> u32 {
+        self.count
+    }
+
+    async fn add_to_count(&mut self, msg: Add) -> Result<u32, CounterError> {
+        self.count = self.count.checked_add(msg.0).ok_or(CounterError)?;
+        if self.count > Self::LIMIT {
+            return Err(CounterError);
+        }
+        Ok(self.count)
+    }
+
+    fn current_count(&mut self, _msg: Get) -> u32 {
+        self.count()
+    }
+
+    #[tell]
+    async fn reset_count(&mut self, #[msg] _reset: Reset) {
+        self.count = 0;
+    }
+
+    #[handler]
+    fn move_count(&mut self, msg: Move) -> u32 {
+        std::mem::replace(&mut self.count, msg.0)
+    }
+}
+
+#[test]
+fn methods_stay_callable() {
+    block_on(async {
+        let mut counter = Counter::starting_at(1);
+
+        assert_eq!(counter.add_to_count(Add(2)).await, Ok(3));
+        assert_eq!(counter.current_count(Get), 3);
+        counter.reset_count(Reset).await;
+        assert_eq!(counter.move_count(Move(4)), 0);
+        assert_eq!(counter.count(), 4);
+    });
+}
+
+#[test]
+fn handle_calls_the_methods() {
+    block_on(async {
+        let counter = Sender::new(Counter::default());
+
+        let added: Result<u32, CounterError> = counter.ask(Add(2)).await;
+        assert_eq!(added, Ok(2));
+        let count: Result<u32, InfallibleError> = counter.ask(Get).await;
+        assert_eq!(count, Ok(2));
+    });
+}
+
+#[test]
+fn handle_methods_are_named_after_messages() {
+    block_on(async {
+        let counter = CounterRef::from(Sender::new(Counter::default()));
+
+        assert_eq!(counter.add(Add(3)).await, Ok(3));
+        assert_eq!(counter.add(Add(u32::MAX)).await, Err(CounterError));
+        assert_eq!(counter.get(Get).await, Ok(3));
+        assert_eq!(counter.reset(Reset).await, Ok(()));
+        assert_eq!(counter.get(Get).await, Ok(0));
+        assert_eq!(counter.r#move(Move(7)).await, Ok(0));
+        assert_eq!(counter.get(Get).await, Ok(7));
+    });
+}
+
+#[derive(Debug, PartialEq)]
+enum ServiceError {
+    Timeout,
+    Db(DbError),
+}
+
+impl From<ascolt::TimeoutError> for ServiceError {
+    fn from(_: ascolt::TimeoutError) -> Self {
+        Self::Timeout
+    }
+}
+
+impl From<DbError> for ServiceError {
+    fn from(err: DbError) -> Self {
+        Self::Db(err)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct DbError;
+
+mod service {
+    pub type Result<T> = std::result::Result<T, super::ServiceError>;
+}
+
+#[derive(Actor)]
+#[actor(error = ServiceError)]
+struct Service;
+
+struct Fetch;
+
+struct Store;
+
+struct Flush;
+
+// The block's error applies to `fetch` and `flush`, whose return types do not
+// name one.
+#[actor_handlers(error = ServiceError)]
+impl Service {
+    #[handler(priority = high, timeout_ms = 500)]
+    async fn fetch(&mut self, _msg: Fetch) -> service::Result<u32> {
+        Ok(1)
+    }
+
+    fn store(&mut self, _msg: Store) -> Result<(), DbError> {
+        Err(DbError)
+    }
+
+    #[handler(priority = low)]
+    fn flush(&mut self, _msg: Flush) {}
+}
+
+#[test]
+fn method_options_override_block_options() {
+    assert_eq!(
+        <Service as AskHandlerTrait<Fetch, u32, ServiceError>>::PRIORITY,
+        Priority::High
+    );
+    assert_eq!(
+        <Service as TellHandlerTrait<Store, DbError>>::PRIORITY,
+        Priority::Normal
+    );
+    assert_eq!(
+        <Service as TellHandlerTrait<Flush, ServiceError>>::PRIORITY,
+        Priority::Low
+    );
+
+    block_on(async {
+        let service = Sender::new(Service);
+        let fetched: service::Result<u32> = service.ask(Fetch).await;
+        assert_eq!(fetched, Ok(1));
+        assert_eq!(service.tell::<Store, DbError>(Store).await, Err(DbError));
+        assert_eq!(service.tell::<Flush, ServiceError>(Flush).await, Ok(()));
+    });
+}
@@ -0,0 +1,87 @@
+use ascolt::block_on;
+use ascolt_macros::{Actor, actor_handlers, actor_messages};
+
+#[derive(Debug, PartialEq)]
+enum StoreError {
+    Db(DbError),
+}
+
+impl From<DbError> for StoreError {
+    fn from(err: DbError) -> Self {
+        Self::Db(err)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct DbError;
+
+struct Insert(String);
+
+struct Count;
+
+struct Clear;
+
+#[actor_messages(ask(Insert -> usize | DbError, Count -> usize), tell(Clear))]
+#[derive(Default, Actor)]
+#[actor(error = StoreError)]
+struct Store {
+    items: Vec<String>,
+}
+
+#[actor_handlers(error = StoreError)]
+impl Store {
+    fn insert(&mut self, msg: Insert) -> Result<usize, DbError> {
+        if self.items.contains(&msg.0) {
+            return Err(DbError);
+        }
+        self.items.push(msg.0);
+        Ok(self.items.len())
+    }
+
+    fn count(&mut self, _msg: Count) -> usize {
+        self.items.len()
+    }
+
+    #[tell]
+    fn clear(&mut self, _msg: Clear) {
+        self.items.clear();
+    }
+}
+
+/// Dispatches `msg`, with a new context when handlers take one.
+async fn dispatch(store: &mut Store, msg: StoreMsg) -> Result<StoreReply, StoreError> {
+    #[cfg(feature = "context")]
+    return store.dispatch(msg, &mut ascolt::Context::new()).await;
+    #[cfg(not(feature = "context"))]
+    return store.dispatch(msg).await;
+}
+
+#[test]
+fn dispatch_routes_messages() {
+    block_on(async {
+        let mut store = Store::default();
+
+        let reply = dispatch(&mut store, Insert("a".into()).into()).await;
+        assert!(matches!(reply, Ok(StoreReply::Insert(1))));
+
+        let reply = dispatch(&mut store, Count.into()).await;
+        assert!(matches!(reply, Ok(StoreReply::Count(1))));
+
+        let reply = dispatch(&mut store, Clear.into()).await;
+        assert!(matches!(reply, Ok(StoreReply::Clear)));
+        assert!(store.items.is_empty());
+    });
+}
+
+#[test]
+fn dispatch_converts_handler_errors() {
+    block_on(async {
+        let mut store = Store::default();
+
+        let reply = dispatch(&mut store, Insert("a".into()).into()).await;
+        assert!(matches!(reply, Ok(StoreReply::Insert(1))));
+
+        let reply = dispatch(&mut store, Insert("a".into()).into()).await;
+        assert!(matches!(reply, Err(StoreError::Db(DbError))));
+    });
+}
@@ -0,0 +1,200 @@
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context as TaskContext, Poll, Waker};
+
+use ascolt::{Sender, TimeoutError, block_on};
+use ascolt_macros::{Actor, actor_handlers, ask_handler, tell_handler};
+
+#[derive(Debug, PartialEq)]
+enum LogError {
+    Timeout,
+}
+
+impl From<TimeoutError> for LogError {
+    fn from(_: TimeoutError) -> Self {
+        Self::Timeout
+    }
+}
+
+#[derive(Default, Actor)]
+#[actor(error = LogError)]
+struct Log {
+    lines: Vec<String>,
+}
+
+struct Line(&'static str);
+
+struct Lines;
+
+struct Clear;
+
+#[tell_handler(error = LogError)]
+async fn handle(self: &mut Log, msg: Line) {
+    self.lines.push(msg.0.to_owned());
+}
+
+#[tell_handler(error = LogError)]
+fn handle(self: &mut Log, _msg: Clear) {
+    self.lines.clear();
+}
+
+#[ask_handler(error = LogError)]
+fn handle(self: &mut Log, _msg: Lines) -> usize {
+    self.lines.len()
+}
+
+#[test]
+fn tell_handlers_without_return_type() {
+    block_on(async {
+        let log = Sender::new(Log::default());
+
+        assert_eq!(log.tell(Line("started")).await, Ok::<_, LogError>(()));
+        assert_eq!(log.tell(Line("stopped")).await, Ok::<_, LogError>(()));
+        assert_eq!(log.ask(Lines).await, Ok::<_, LogError>(2));
+        assert_eq!(log.tell(Clear).await, Ok::<_, LogError>(()));
+        assert_eq!(log.ask(Lines).await, Ok::<_, LogError>(0));
+    });
+}
+
+/// Polls `fut` once.
+fn poll_once<F: Future>(fut: F) -> Poll<F::Output> {
+    pin!(fut).poll(&mut TaskContext::from_waker(Waker::noop()))
+}
+
+#[test]
+fn sync_handlers_complete_on_first_poll() {
+    let log = Sender::new(Log::default());
+
+    assert_eq!(poll_once(log.ask(Lines)), Poll::Ready(Ok::<_, LogError>(0)));
+    assert_eq!(
+        poll_once(log.tell(Clear)),
+        Poll::Ready(Ok::<_, LogError>(()))
+    );
+}
+
+// With `async fn` in traits, synchronous handlers run when called rather than
+// when their future is first polled.
+#[cfg(all(feature = "native-async", not(feature = "context")))]
+#[test]
+fn sync_handlers_run_on_call() {
+    use ascolt::handler::TellHandlerTrait;
+
+    let mut log = Log {
+        lines: vec!["started".into()],
+    };
+    drop(TellHandlerTrait::<Clear, LogError>::handle(&mut log, Clear));
+    assert!(log.lines.is_empty());
+}
+
+struct Fetch {
+    delay: bool,
+}
+
+#[ask_handler(timeout_ms = 10)]
+async fn handle(self: &mut Log, msg: Fetch) -> Result<usize, LogError> {
+    if msg.delay {
+        std::future::pending::<()>().await;
+    }
+    Ok(self.lines.len())
+}
+
+#[test]
+fn slow_handlers_time_out() {
+    block_on(async {
+        let log = Sender::new(Log::default());
+
+        assert_eq!(log.ask(Fetch { delay: false }).await, Ok(0));
+        assert_eq!(log.ask(Fetch { delay: true }).await, Err(LogError::Timeout));
+    });
+}
+
+#[allow(dead_code)]
+struct Missing;
+
+struct Rotate;
+
+// Neither the impl nor its handle method exist when the `#[cfg]` is false, so
+// the handler may name items that only exist under it.
+#[ask_handler(error = LogError, handle = LogRef)]
+#[cfg(any())]
+fn handle(self: &mut Log, _msg: Missing) -> NotDefined {
+    NotDefined
+}
+
+// Lints allowed on the handler apply to the generated method as well.
+#[tell_handler(error = LogError)]
+#[allow(unused_variables)]
+fn handle(self: &mut Log, _msg: Rotate) {
+    let rotated = self.lines.drain(..);
+}
+
+#[test]
+fn forwarded_attributes() {
+    block_on(async {
+        let log = Sender::new(Log {
+            lines: vec!["old".into()],
+        });
+        assert_eq!(log.tell(Rotate).await, Ok::<_, LogError>(()));
+        assert_eq!(log.ask(Lines).await, Ok::<_, LogError>(0));
+    });
+}
+
+#[derive(Default, Actor)]
+#[actor(error = LogError)]
+struct Stack<T: Send + 'static> {
+    items: Vec<T>,
+}
+
+struct Push<T>(T);
+
+struct Pop;
+
+#[actor_handlers(error = LogError)]
+impl<T: Send + 'static> Stack<T> {
+    fn push(&mut self, msg: Push<T>) {
+        self.items.push(msg.0);
+    }
+
+    fn pop(&mut self, _msg: Pop) -> Option<T> {
+        self.items.pop()
+    }
+}
+
+#[test]
+fn generic_actors() {
+    block_on(async {
+        let stack = Sender::new(Stack::<u32>::default());
+
+        assert_eq!(stack.tell(Push(1)).await, Ok::<_, LogError>(()));
+        assert_eq!(stack.tell(Push(2)).await, Ok::<_, LogError>(()));
+        assert_eq!(stack.ask(Pop).await, Ok::<_, LogError>(Some(2)));
+    });
+}
+
+#[cfg(feature = "context")]
+mod context {
+    use ascolt::{Context, Sender, block_on};
+    use ascolt_macros::{Actor, ask_handler};
+
+    use super::LogError;
+
+    #[derive(Actor)]
+    #[actor(error = LogError)]
+    struct Greeter;
+
+    struct Greet;
+
+    #[ask_handler(error = LogError)]
+    fn handle(self: &mut Greeter, ctx: &mut Context<Greeter>, _msg: Greet) -> &'static str {
+        let _: &mut Context<Greeter> = ctx;
+        "hello"
+    }
+
+    #[test]
+    fn handlers_take_the_context() {
+        block_on(async {
+            let greeter = Sender::new(Greeter);
+            assert_eq!(greeter.ask(Greet).await, Ok::<_, LogError>("hello"));
+        });
+    }
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "metrics")]
+
+use std::sync::{Arc, Mutex};
+
+use ascolt::metrics::{
+    Counter, CounterFn, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit, with_local_recorder,
+};
+use ascolt::{Sender, block_on};
+use ascolt_macros::{Actor, ask_handler};
+
+/// Metric name and `message` label of every recorded value.
+type Records = Arc<Mutex<Vec<(String, String)>>>;
+
+struct Record {
+    name: String,
+    message: String,
+    records: Records,
+}
+
+impl Record {
+    fn push(&self) {
+        self.records
+            .lock()
+            .unwrap()
+            .push((self.name.clone(), self.message.clone()));
+    }
+}
+
+impl CounterFn for Record {
+    fn increment(&self, _value: u64) {
+        self.push();
+    }
+
+    fn absolute(&self, _value: u64) {
+        self.push();
+    }
+}
+
+impl HistogramFn for Record {
+    fn record(&self, _value: f64) {
+        self.push();
+    }
+}
+
+#[derive(Default)]
+struct TestRecorder {
+    records: Records,
+}
+
+impl TestRecorder {
+    fn record(&self, key: &Key) -> Arc<Record> {
+        let message = key
+            .labels()
+            .find(|label| label.key() == "message")
+            .map(|label| label.value().to_owned())
+            .unwrap_or_default();
+        Arc::new(Record {
+            name: key.name().to_owned(),
+            message,
+            records: Arc::clone(&self.records),
+        })
+    }
+
+    fn count(&self, name: &str, message: &str) -> usize {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(n, m)| n == name && m.ends_with(message))
+            .count()
+    }
+}
+
+impl Recorder for TestRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.record(key))
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(self.record(key))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct ParseError;
+
+#[derive(Actor)]
+#[actor(error = ParseError)]
+struct Parser;
+
+struct Parse(&'static str);
+
+#[ask_handler]
+async fn handle(self: &mut Parser, msg: Parse) -> Result<u32, ParseError> {
+    msg.0.parse().map_err(|_| ParseError)
+}
+
+#[test]
+fn handlers_record_calls_errors_and_durations() {
+    let recorder = TestRecorder::default();
+
+    with_local_recorder(&recorder, || {
+        block_on(async {
+            let parser = Sender::new(Parser);
+            assert_eq!(parser.ask(Parse("1")).await, Ok(1));
+            assert_eq!(parser.ask(Parse("x")).await, Err(ParseError));
+        });
+    });
+
+    assert_eq!(recorder.count("ascolt_handler_calls_total", "::Parse"), 2);
+    assert_eq!(recorder.count("ascolt_handler_errors_total", "::Parse"), 1);
+    assert_eq!(
+        recorder.count("ascolt_handler_duration_seconds", "::Parse"),
+        2
+    );
+}
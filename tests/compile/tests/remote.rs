@@ -0,0 +1,119 @@
+use std::sync::mpsc;
+use std::thread;
+
+use ascolt::block_on;
+use ascolt::remote::{CodecError, RemoteMessageTrait, decode};
+use ascolt_macros::{Actor, RemoteMessage, actor_handlers, actor_messages};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct LedgerError(String);
+
+#[derive(Serialize, Deserialize, RemoteMessage)]
+struct Deposit(u32);
+
+#[derive(Serialize, Deserialize, RemoteMessage)]
+#[remote(tag = "ledger.reset")]
+struct Reset;
+
+#[actor_messages(ask(Deposit -> u32), tell(Reset), remote)]
+#[derive(Default, Actor)]
+#[actor(error = LedgerError)]
+struct Ledger {
+    balance: u32,
+}
+
+#[actor_handlers(error = LedgerError)]
+impl Ledger {
+    fn deposit(&mut self, msg: Deposit) -> Result<u32, LedgerError> {
+        self.balance = self
+            .balance
+            .checked_add(msg.0)
+            .ok_or_else(|| LedgerError("overflow".into()))?;
+        Ok(self.balance)
+    }
+
+    fn reset(&mut self, _msg: Reset) {
+        self.balance = 0;
+    }
+}
+
+/// Dispatches a received message, with a new context when handlers take one.
+async fn dispatch(ledger: &mut Ledger, tag: &str, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+    #[cfg(feature = "context")]
+    return ledger
+        .dispatch_remote(tag, bytes, &mut ascolt::Context::new())
+        .await;
+    #[cfg(not(feature = "context"))]
+    return ledger.dispatch_remote(tag, bytes).await;
+}
+
+type Packet = (String, Vec<u8>, mpsc::Sender<Result<Vec<u8>, CodecError>>);
+
+/// A ledger on its own thread, reached only through encoded messages.
+struct RemoteLedger {
+    packets: mpsc::Sender<Packet>,
+}
+
+impl RemoteLedger {
+    fn spawn() -> Self {
+        let (packets, received) = mpsc::channel::<Packet>();
+        thread::spawn(move || {
+            let mut ledger = Ledger::default();
+            for (tag, bytes, reply) in received {
+                let _ = reply.send(block_on(dispatch(&mut ledger, &tag, &bytes)));
+            }
+        });
+        Self { packets }
+    }
+
+    fn send(&self, tag: &str, bytes: Vec<u8>) -> Result<Vec<u8>, CodecError> {
+        let (reply, replied) = mpsc::channel();
+        self.packets.send((tag.to_owned(), bytes, reply)).unwrap();
+        replied.recv().unwrap()
+    }
+
+    fn ask<M, R>(&self, msg: M) -> Result<Result<R, LedgerError>, CodecError>
+    where
+        M: RemoteMessageTrait,
+        R: DeserializeOwned,
+    {
+        let reply = self.send(M::TAG, msg.encode()?)?;
+        decode(&reply)
+    }
+}
+
+#[test]
+fn messages_round_trip() {
+    let ledger = RemoteLedger::spawn();
+
+    assert_eq!(ledger.ask(Deposit(5)), Ok(Ok(5)));
+    assert_eq!(ledger.ask(Deposit(2)), Ok(Ok(7)));
+    assert_eq!(
+        ledger.ask::<_, u32>(Deposit(u32::MAX)),
+        Ok(Err(LedgerError("overflow".into())))
+    );
+    assert_eq!(ledger.ask::<_, ()>(Reset), Ok(Ok(())));
+    assert_eq!(ledger.ask(Deposit(1)), Ok(Ok(1)));
+}
+
+#[test]
+fn tags_default_to_type_names() {
+    assert_eq!(Deposit::TAG, "Deposit");
+    assert_eq!(Reset::TAG, "ledger.reset");
+}
+
+#[test]
+fn unknown_tags_are_rejected() {
+    let ledger = RemoteLedger::spawn();
+
+    assert_eq!(
+        ledger.send("ledger.audit", Vec::new()),
+        Err(CodecError::UnknownTag("ledger.audit".into()))
+    );
+    assert_eq!(
+        ledger.send(Deposit::TAG, b"five".to_vec()),
+        Err(CodecError::Malformed)
+    );
+}
@@ -0,0 +1,156 @@
+use ascolt::{Sender, block_on};
+use ascolt_macros::{Actor, actor_handlers, ask_handler};
+
+#[derive(Debug, PartialEq)]
+struct DbError;
+
+type DbResult<T> = Result<T, DbError>;
+
+fn load_row() -> DbResult<u32> {
+    Ok(5)
+}
+
+// Error of handlers naming none, the actor's when ascolt can name it.
+#[cfg(feature = "actor-error")]
+type DefaultError = DbError;
+#[cfg(not(feature = "actor-error"))]
+type DefaultError = std::convert::Infallible;
+
+#[derive(Debug, PartialEq)]
+struct SearchResult {
+    hits: u32,
+}
+
+#[derive(Debug, PartialEq)]
+struct QueryResult<T>(T);
+
+#[derive(Actor)]
+#[actor(error = DbError)]
+struct Db;
+
+struct Search;
+
+struct Query;
+
+struct Load;
+
+struct Fail;
+
+#[ask_handler]
+fn handle(self: &mut Db, _msg: Search) -> SearchResult {
+    SearchResult { hits: 3 }
+}
+
+#[ask_handler]
+fn handle(self: &mut Db, _msg: Query) -> QueryResult<u32> {
+    QueryResult(4)
+}
+
+#[ask_handler(error = DbError)]
+fn handle(self: &mut Db, _msg: Load) -> DbResult<u32> {
+    load_row()
+}
+
+#[ask_handler]
+fn handle(self: &mut Db, _msg: Fail) -> std::result::Result<u32, DbError> {
+    Err(DbError)
+}
+
+#[test]
+fn result_names_are_responses() {
+    block_on(async {
+        let db = Sender::new(Db);
+
+        let search: Result<SearchResult, DefaultError> = db.ask(Search).await;
+        assert_eq!(search, Ok(SearchResult { hits: 3 }));
+
+        let query: Result<QueryResult<u32>, DefaultError> = db.ask(Query).await;
+        assert_eq!(query, Ok(QueryResult(4)));
+    });
+}
+
+#[test]
+fn result_aliases_need_error() {
+    block_on(async {
+        let db = Sender::new(Db);
+
+        let load: Result<u32, DbError> = db.ask(Load).await;
+        assert_eq!(load, Ok(5));
+
+        let fail: Result<u32, DbError> = db.ask(Fail).await;
+        assert_eq!(fail, Err(DbError));
+    });
+}
+
+#[derive(Actor)]
+#[actor(error = DbError)]
+struct Index;
+
+struct Find;
+
+struct Rank;
+
+struct Reload;
+
+// The block's error is used by handlers naming none, but does not make the
+// `Result`-suffixed names below read as results.
+#[actor_handlers(error = DbError)]
+impl Index {
+    fn find(&mut self, _msg: Find) -> SearchResult {
+        SearchResult { hits: 1 }
+    }
+
+    fn rank(&mut self, _msg: Rank) -> QueryResult<u32> {
+        QueryResult(2)
+    }
+
+    fn reload(&mut self, _msg: Reload) -> DbResult<u32> {
+        Err(DbError)
+    }
+}
+
+#[test]
+fn block_error_keeps_result_names_as_responses() {
+    block_on(async {
+        let index = Sender::new(Index);
+
+        let find: Result<SearchResult, DbError> = index.ask(Find).await;
+        assert_eq!(find, Ok(SearchResult { hits: 1 }));
+
+        let rank: Result<QueryResult<u32>, DbError> = index.ask(Rank).await;
+        assert_eq!(rank, Ok(QueryResult(2)));
+
+        let reload: Result<DbResult<u32>, DbError> = index.ask(Reload).await;
+        assert_eq!(reload, Ok(Err(DbError)));
+    });
+}
+
+#[cfg(feature = "actor-error")]
+mod actor_error {
+    use ascolt::{Sender, block_on};
+    use ascolt_macros::ask_handler;
+
+    use super::{Db, DbError};
+
+    struct Count;
+
+    // With `ActorErrorTrait`, a `Result` alias without `error` fails with the
+    // actor's error.
+    #[ask_handler]
+    fn handle(self: &mut Db, _msg: Count) -> anyhow_like::Result<u32> {
+        let count: anyhow_like::Result<u32> = Err(DbError);
+        count
+    }
+
+    mod anyhow_like {
+        pub type Result<T> = std::result::Result<T, super::DbError>;
+    }
+
+    #[test]
+    fn result_aliases_take_the_actor_error() {
+        block_on(async {
+            let count: Result<u32, DbError> = Sender::new(Db).ask(Count).await;
+            assert_eq!(count, Err(DbError));
+        });
+    }
+}
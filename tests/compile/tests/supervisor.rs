@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use ascolt::supervisor::{Restart, Strategy, SupervisorTrait};
+use ascolt_macros::Supervisor;
+
+#[derive(Clone)]
+struct Db;
+
+#[derive(Clone)]
+struct Mailer;
+
+#[derive(Clone)]
+struct Cache;
+
+#[derive(Supervisor)]
+#[supervisor(strategy = "rest_for_one", max_restarts = 3, within_ms = 10_000)]
+struct Services {
+    #[child(restart = "permanent")]
+    db: Db,
+    #[child(restart = "transient")]
+    mailer: Mailer,
+    #[child(restart = "temporary")]
+    cache: Cache,
+    #[allow(dead_code)]
+    label: &'static str,
+}
+
+#[test]
+fn spec_lists_children_in_field_order() {
+    let services = Services {
+        db: Db,
+        mailer: Mailer,
+        cache: Cache,
+        label: "services",
+    };
+    let spec = services.supervisor_spec();
+
+    assert_eq!(spec.strategy, Strategy::RestForOne);
+    assert_eq!(spec.max_restarts, 3);
+    assert_eq!(spec.within, Duration::from_secs(10));
+
+    let children: Vec<_> = spec
+        .children
+        .iter()
+        .map(|child| (child.name, child.restart))
+        .collect();
+    assert_eq!(
+        children,
+        [
+            ("db", Restart::Permanent),
+            ("mailer", Restart::Transient),
+            ("cache", Restart::Temporary),
+        ]
+    );
+}
+
+#[derive(Supervisor)]
+struct Defaults {
+    #[child(strategy = "one_for_all")]
+    db: Db,
+    #[child]
+    mailer: Mailer,
+}
+
+#[test]
+fn spec_defaults() {
+    let spec = Defaults {
+        db: Db,
+        mailer: Mailer,
+    }
+    .supervisor_spec();
+
+    assert_eq!(spec.strategy, Strategy::OneForAll);
+    assert_eq!(spec.max_restarts, 1);
+    assert_eq!(spec.within, Duration::from_millis(5000));
+    assert!(
+        spec.children
+            .iter()
+            .all(|child| child.restart == Restart::Permanent)
+    );
+}
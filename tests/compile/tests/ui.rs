@@ -0,0 +1,9 @@
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+    #[cfg(feature = "actor-error")]
+    cases.compile_fail("tests/ui/actor-error/*.rs");
+    #[cfg(feature = "tracing")]
+    cases.compile_fail("tests/ui/tracing/*.rs");
+}
@@ -0,0 +1,21 @@
+use ascolt_macros::{Actor, ask_handler};
+
+#[derive(Debug)]
+struct Error;
+
+#[derive(Debug)]
+struct DbError;
+
+#[derive(Actor)]
+#[actor(error = Error)]
+struct Db;
+
+struct Load;
+
+// `Error` has no `From<DbError>`, so the actor could not report the failure.
+#[ask_handler]
+fn handle(self: &mut Db, _msg: Load) -> Result<u32, DbError> {
+    Err(DbError)
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use ascolt_macros::Actor;
+
+#[derive(Debug)]
+struct Error;
+
+#[derive(Actor)]
+#[actor(error = Error, name = "")]
+struct Unnamed;
+
+#[derive(Actor)]
+#[actor(error = Error, mailbox = bounded(0))]
+struct Empty;
+
+#[derive(Actor)]
+#[actor(error = Error, overflow = "drop_oldest")]
+struct Unbounded;
+
+#[derive(Actor)]
+#[actor(error = Error, mailbox = unbounded, overflow = "error")]
+struct ExplicitlyUnbounded;
+
+fn main() {}
@@ -0,0 +1,19 @@
+use ascolt_macros::{Actor, Message, ask_handler};
+
+#[derive(Debug)]
+struct Error;
+
+#[derive(Actor)]
+#[actor(error = Error)]
+struct Users;
+
+#[derive(Message)]
+#[message(response = u32, error = Error)]
+struct CountUsers;
+
+#[ask_handler(error = Error, checked)]
+fn handle(self: &mut Users, _msg: CountUsers) -> String {
+    String::new()
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+use ascolt_macros::{Actor, RemoteMessage, actor_handlers, actor_messages};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Error;
+
+#[derive(Serialize, Deserialize, RemoteMessage)]
+#[remote(tag = "ping")]
+struct Ping;
+
+#[derive(Serialize, Deserialize, RemoteMessage)]
+#[remote(tag = "ping")]
+struct Pong;
+
+#[actor_messages(tell(Ping, Pong), remote)]
+#[derive(Actor)]
+#[actor(error = Error)]
+struct Player;
+
+#[actor_handlers(error = Error)]
+impl Player {
+    fn ping(&mut self, _msg: Ping) {}
+
+    fn pong(&mut self, _msg: Pong) {}
+}
+
+fn main() {}
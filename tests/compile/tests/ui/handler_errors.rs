@@ -0,0 +1,33 @@
+use ascolt_macros::{Actor, actor_handlers, ask_handler};
+
+#[derive(Debug)]
+struct Error;
+
+#[derive(Actor)]
+#[actor(error = Error)]
+struct Worker;
+
+struct Job;
+
+struct Stop;
+
+// Every handler's errors are reported, not only the first one.
+#[actor_handlers(error = Error)]
+impl Worker {
+    #[ask]
+    #[tell]
+    fn run(&mut self, _msg: Job) -> u32 {
+        1
+    }
+
+    #[handler(timeout_ms = 10)]
+    fn stop(&mut self, #[msg] _stop: Stop, #[msg] _job: Job) {}
+}
+
+// The arguments and the return type are checked together.
+#[ask_handler]
+fn handle(self: &mut Worker, _first: Job, _second: Stop) -> Result<u32, Error, Error> {
+    Ok(1)
+}
+
+fn main() {}
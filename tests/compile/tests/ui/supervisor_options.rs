@@ -0,0 +1,25 @@
+use ascolt_macros::Supervisor;
+
+#[derive(Clone)]
+struct Db;
+
+#[derive(Supervisor)]
+#[supervisor(strategy = "one_for_all")]
+struct Conflicting {
+    #[child(strategy = "rest_for_one")]
+    db: Db,
+}
+
+#[derive(Supervisor)]
+#[supervisor(strategy = "one_for_one")]
+struct Childless {
+    db: Db,
+}
+
+#[derive(Supervisor)]
+struct UnknownRestart {
+    #[child(restart = "always")]
+    db: Db,
+}
+
+fn main() {}
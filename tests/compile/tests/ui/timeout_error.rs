@@ -0,0 +1,18 @@
+use ascolt_macros::{Actor, ask_handler};
+
+#[derive(Debug)]
+struct Error;
+
+#[derive(Actor)]
+#[actor(error = Error)]
+struct Fetcher;
+
+struct Fetch;
+
+// `Error` has no `From<ascolt::TimeoutError>` to report the timeout with.
+#[ask_handler(timeout_ms = 100)]
+async fn handle(self: &mut Fetcher, _msg: Fetch) -> Result<u32, Error> {
+    Ok(1)
+}
+
+fn main() {}
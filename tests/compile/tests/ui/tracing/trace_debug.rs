@@ -0,0 +1,26 @@
+use ascolt_macros::{Actor, ask_handler};
+
+#[derive(Debug)]
+struct Error;
+
+struct Opaque;
+
+impl From<Opaque> for Error {
+    fn from(_: Opaque) -> Self {
+        Self
+    }
+}
+
+#[derive(Actor)]
+#[actor(error = Error)]
+struct Traced;
+
+struct Load;
+
+// Failed calls are recorded with the error's `Debug` output.
+#[ask_handler(trace)]
+fn handle(self: &mut Traced, _msg: Load) -> Result<u32, Opaque> {
+    Err(Opaque)
+}
+
+fn main() {}
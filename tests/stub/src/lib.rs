@@ -0,0 +1,355 @@
+//! Stand-in for the parts of the ascolt API that ascolt-macros generates code
+//! against. Without features it has the shape of the published ascolt; the
+//! `context` and `actor-error` features add the API of ascolt versions that
+//! enable the ascolt-macros features of the same name.
+//!
+//! Actors run in place: a `Sender` owns its actor and calls the handler
+//! directly, so every future completes without a runtime.
+
+use std::future::{Future, poll_fn};
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context as TaskContext, Poll, Waker};
+use std::time::{Duration, Instant};
+
+pub use async_trait;
+pub use metrics;
+pub use serde;
+pub use tracing;
+
+#[cfg(feature = "actor-error")]
+pub trait ActorErrorTrait {
+    type Error;
+}
+
+#[cfg_attr(not(feature = "native-async"), async_trait::async_trait)]
+#[allow(async_fn_in_trait)]
+pub trait ActorTrait<E: Send + 'static>: Send + Sized + 'static {
+    const MAILBOX: mailbox::MailboxConfig = mailbox::MailboxConfig::unbounded();
+
+    async fn on_start(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    async fn on_stop(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    async fn on_error(&mut self, err: E) -> Result<(), E> {
+        Err(err)
+    }
+}
+
+pub trait MessageTrait {
+    type Response;
+    type Error;
+    const PRIORITY: mailbox::Priority = mailbox::Priority::Normal;
+}
+
+pub mod handler {
+    #[cfg(feature = "context")]
+    use crate::Context;
+
+    #[cfg_attr(not(feature = "native-async"), crate::async_trait::async_trait)]
+    #[allow(async_fn_in_trait)]
+    pub trait AskHandlerTrait<M, R, E>: Send + Sized {
+        const PRIORITY: crate::mailbox::Priority = crate::mailbox::Priority::Normal;
+
+        #[cfg(not(feature = "context"))]
+        async fn handle(&mut self, msg: M) -> Result<R, E>;
+
+        #[cfg(feature = "context")]
+        async fn handle(&mut self, msg: M, ctx: &mut Context<Self>) -> Result<R, E>;
+    }
+
+    #[cfg_attr(not(feature = "native-async"), crate::async_trait::async_trait)]
+    #[allow(async_fn_in_trait)]
+    pub trait TellHandlerTrait<M, E>: Send + Sized {
+        const PRIORITY: crate::mailbox::Priority = crate::mailbox::Priority::Normal;
+
+        #[cfg(not(feature = "context"))]
+        async fn handle(&mut self, msg: M) -> Result<(), E>;
+
+        #[cfg(feature = "context")]
+        async fn handle(&mut self, msg: M, ctx: &mut Context<Self>) -> Result<(), E>;
+    }
+}
+
+/// Context handed to handlers.
+#[cfg(feature = "context")]
+pub struct Context<A>(std::marker::PhantomData<fn() -> A>);
+
+#[cfg(feature = "context")]
+impl<A> Context<A> {
+    pub fn new() -> Self {
+        Self(std::marker::PhantomData)
+    }
+}
+
+#[cfg(feature = "context")]
+impl<A> Default for Context<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sends messages to an actor it owns.
+pub struct Sender<A> {
+    actor: Arc<Mutex<Option<A>>>,
+}
+
+impl<A> Clone for Sender<A> {
+    fn clone(&self) -> Self {
+        Self {
+            actor: Arc::clone(&self.actor),
+        }
+    }
+}
+
+impl<A> Sender<A> {
+    pub fn new(actor: A) -> Self {
+        Self {
+            actor: Arc::new(Mutex::new(Some(actor))),
+        }
+    }
+
+    pub async fn ask<M, R, E>(&self, msg: M) -> Result<R, E>
+    where
+        A: handler::AskHandlerTrait<M, R, E>,
+    {
+        let mut actor = self.take();
+        #[cfg(not(feature = "context"))]
+        let result = handler::AskHandlerTrait::handle(&mut actor, msg).await;
+        #[cfg(feature = "context")]
+        let result = handler::AskHandlerTrait::handle(&mut actor, msg, &mut Context::new()).await;
+        self.put_back(actor);
+        result
+    }
+
+    pub async fn tell<M, E>(&self, msg: M) -> Result<(), E>
+    where
+        A: handler::TellHandlerTrait<M, E>,
+    {
+        let mut actor = self.take();
+        #[cfg(not(feature = "context"))]
+        let result = handler::TellHandlerTrait::handle(&mut actor, msg).await;
+        #[cfg(feature = "context")]
+        let result = handler::TellHandlerTrait::handle(&mut actor, msg, &mut Context::new()).await;
+        self.put_back(actor);
+        result
+    }
+
+    /// Whether both senders reach the same actor.
+    pub fn same_actor(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.actor, &other.actor)
+    }
+
+    fn take(&self) -> A {
+        self.actor
+            .lock()
+            .unwrap()
+            .take()
+            .expect("actor is handling another message")
+    }
+
+    fn put_back(&self, actor: A) {
+        *self.actor.lock().unwrap() = Some(actor);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutError;
+
+/// Runs `fut` until it completes or `duration` has elapsed.
+pub async fn timeout<F: Future>(duration: Duration, fut: F) -> Result<F::Output, TimeoutError> {
+    let deadline = Instant::now() + duration;
+    let mut fut = pin!(fut);
+    poll_fn(|cx| match fut.as_mut().poll(cx) {
+        Poll::Ready(output) => Poll::Ready(Ok(output)),
+        Poll::Pending if Instant::now() >= deadline => Poll::Ready(Err(TimeoutError)),
+        Poll::Pending => Poll::Pending,
+    })
+    .await
+}
+
+/// Polls `fut` on the current thread until it completes.
+pub fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = pin!(fut);
+    let mut cx = TaskContext::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+pub mod mailbox {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Priority {
+        Low,
+        Normal,
+        High,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Overflow {
+        DropOldest,
+        DropNewest,
+        Block,
+        Error,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct MailboxConfig {
+        pub capacity: Option<usize>,
+        pub overflow: Overflow,
+    }
+
+    impl MailboxConfig {
+        pub const fn bounded(capacity: usize) -> Self {
+            Self {
+                capacity: Some(capacity),
+                overflow: Overflow::Block,
+            }
+        }
+
+        pub const fn unbounded() -> Self {
+            Self {
+                capacity: None,
+                overflow: Overflow::Block,
+            }
+        }
+
+        pub const fn with_overflow(self, overflow: Overflow) -> Self {
+            Self {
+                capacity: self.capacity,
+                overflow,
+            }
+        }
+    }
+}
+
+pub mod registry {
+    use std::any::Any;
+    use std::sync::Mutex;
+
+    use crate::Sender;
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct RegistryError;
+
+    type Entry = (&'static str, Box<dyn Any + Send>);
+
+    static REGISTRY: Mutex<Vec<Entry>> = Mutex::new(Vec::new());
+
+    pub trait NamedActorTrait: Send + Sized + 'static {
+        const NAME: &'static str;
+
+        fn register(sender: Sender<Self>) -> Result<(), RegistryError> {
+            let mut registry = REGISTRY.lock().unwrap();
+            if registry.iter().any(|(name, _)| *name == Self::NAME) {
+                return Err(RegistryError);
+            }
+            registry.push((Self::NAME, Box::new(sender)));
+            Ok(())
+        }
+
+        fn lookup() -> Option<Sender<Self>> {
+            let registry = REGISTRY.lock().unwrap();
+            registry
+                .iter()
+                .find(|(name, _)| *name == Self::NAME)
+                .and_then(|(_, sender)| sender.downcast_ref::<Sender<Self>>())
+                .cloned()
+        }
+    }
+}
+
+pub mod remote {
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum CodecError {
+        UnknownTag(String),
+        Malformed,
+    }
+
+    impl CodecError {
+        pub fn unknown_tag(tag: &str) -> Self {
+            Self::UnknownTag(tag.to_owned())
+        }
+    }
+
+    pub trait RemoteMessageTrait: Sized {
+        const TAG: &'static str;
+
+        fn encode(&self) -> Result<Vec<u8>, CodecError>;
+
+        fn decode(bytes: &[u8]) -> Result<Self, CodecError>;
+    }
+
+    pub fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, CodecError> {
+        serde_json::to_vec(value).map_err(|_| CodecError::Malformed)
+    }
+
+    pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError> {
+        serde_json::from_slice(bytes).map_err(|_| CodecError::Malformed)
+    }
+}
+
+pub mod supervisor {
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Strategy {
+        OneForOne,
+        OneForAll,
+        RestForOne,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Restart {
+        Permanent,
+        Transient,
+        Temporary,
+    }
+
+    pub struct ChildSpec {
+        pub name: &'static str,
+        pub restart: Restart,
+    }
+
+    impl ChildSpec {
+        pub fn new<A: Send + 'static>(name: &'static str, restart: Restart, _actor: A) -> Self {
+            Self { name, restart }
+        }
+    }
+
+    pub struct SupervisorSpec {
+        pub strategy: Strategy,
+        pub max_restarts: u32,
+        pub within: Duration,
+        pub children: Vec<ChildSpec>,
+    }
+
+    impl SupervisorSpec {
+        pub fn new(strategy: Strategy, max_restarts: u32, within: Duration) -> Self {
+            Self {
+                strategy,
+                max_restarts,
+                within,
+                children: Vec::new(),
+            }
+        }
+
+        pub fn child(mut self, child: ChildSpec) -> Self {
+            self.children.push(child);
+            self
+        }
+    }
+
+    pub trait SupervisorTrait {
+        fn supervisor_spec(&self) -> SupervisorSpec;
+    }
+}